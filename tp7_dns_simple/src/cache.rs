@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::{DnsAnswer, DnsMessage, DnsQuestion, RData, Rcode, DNS_TYPE_SOA};
+
+/// TTL maximal conservé en cache, quelle que soit la valeur annoncée (1 jour)
+const TTL_MAXIMAL: u32 = 86400;
+
+/// Clé d'une entrée : nom (insensible à la casse), type et classe demandés
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    name: String,
+    qtype: u16,
+    qclass: u16,
+}
+
+impl CacheKey {
+    fn new(name: &str, qtype: u16, qclass: u16) -> Self {
+        CacheKey { name: name.trim_end_matches('.').to_ascii_lowercase(), qtype, qclass }
+    }
+}
+
+/// Réponse conservée en cache
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    rcode: Rcode,
+    answers: Vec<DnsAnswer>,
+    authorities: Vec<DnsAnswer>,
+    stored: Instant,
+    expires: Instant,
+    last_used: u64,
+}
+
+/// Réponse extraite du cache, avec des TTL diminués du temps passé en cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedResponse {
+    pub rcode: Rcode,
+    pub answers: Vec<DnsAnswer>,
+    pub authorities: Vec<DnsAnswer>,
+}
+
+impl CachedResponse {
+    /// Indique s'il s'agit d'une réponse négative (NXDOMAIN ou absence de données)
+    pub fn is_negative(&self) -> bool {
+        self.rcode != Rcode::NoError || self.answers.is_empty()
+    }
+
+    /// Construit la réponse à `query` à partir des données en cache
+    pub fn to_response(&self, query: &DnsMessage) -> DnsMessage {
+        let mut response = DnsMessage::new_response(query);
+        response.set_rcode(self.rcode);
+        for answer in &self.answers {
+            response.add_answer(answer.clone());
+        }
+        for authority in &self.authorities {
+            response.add_authority(authority.clone());
+        }
+        response
+    }
+}
+
+/// Compteurs d'utilisation du cache
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+/// Cache de réponses DNS respectant les TTL, avec cache négatif (RFC 2308)
+/// et éviction de l'entrée la moins récemment utilisée lorsque la taille maximale est atteinte
+#[derive(Debug)]
+pub struct DnsCache {
+    capacity: usize,
+    entries: HashMap<CacheKey, CacheEntry>,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl DnsCache {
+    /// Crée un cache pouvant contenir `capacity` réponses
+    pub fn new(capacity: usize) -> Self {
+        DnsCache {
+            capacity,
+            entries: HashMap::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Cherche une réponse encore valide pour la question donnée
+    pub fn get(&mut self, name: &str, qtype: u16, qclass: u16) -> Option<CachedResponse> {
+        self.get_at(name, qtype, qclass, Instant::now())
+    }
+
+    /// Variante de `get` à un instant donné
+    pub fn get_at(&mut self, name: &str, qtype: u16, qclass: u16, now: Instant) -> Option<CachedResponse> {
+        let key = CacheKey::new(name, qtype, qclass);
+
+        let expired = match self.entries.get(&key) {
+            Some(entry) => entry.expires <= now,
+            None => {
+                self.misses += 1;
+                return None;
+            }
+        };
+        if expired {
+            self.entries.remove(&key);
+            self.misses += 1;
+            return None;
+        }
+
+        self.clock += 1;
+        self.hits += 1;
+        let entry = self.entries.get_mut(&key).unwrap();
+        entry.last_used = self.clock;
+
+        let elapsed = now.duration_since(entry.stored).as_secs().min(u32::MAX as u64) as u32;
+        let decay = |records: &[DnsAnswer]| -> Vec<DnsAnswer> {
+            records
+                .iter()
+                .map(|record| {
+                    let mut record = record.clone();
+                    record.ttl = record.ttl.saturating_sub(elapsed);
+                    record
+                })
+                .collect()
+        };
+
+        Some(CachedResponse {
+            rcode: entry.rcode,
+            answers: decay(&entry.answers),
+            authorities: decay(&entry.authorities),
+        })
+    }
+
+    /// Met en cache la réponse à `question`, la question de la requête envoyée : la section
+    /// question de la réponse n'est pas prise comme clé. Retourne `false` si la réponse n'est
+    /// pas cachable (erreur serveur, TTL nul, réponse négative sans SOA).
+    pub fn insert(&mut self, question: &DnsQuestion, response: &DnsMessage) -> bool {
+        self.insert_at(question, response, Instant::now())
+    }
+
+    /// Variante de `insert` à un instant donné
+    pub fn insert_at(&mut self, question: &DnsQuestion, response: &DnsMessage, now: Instant) -> bool {
+        if self.capacity == 0 {
+            return false;
+        }
+
+        let rcode = response.rcode();
+        let ttl = match rcode {
+            Rcode::NoError if !response.answers.is_empty() => response.answers.iter().map(|answer| answer.ttl).min(),
+            // Cache négatif : durée = min(TTL du SOA, champ minimum du SOA)
+            Rcode::NoError | Rcode::NxDomain => Self::negative_ttl(&response.authorities),
+            _ => None,
+        };
+        let ttl = match ttl {
+            Some(ttl) if ttl > 0 => ttl.min(TTL_MAXIMAL),
+            _ => return false,
+        };
+
+        let key = CacheKey::new(&question.qname, question.qtype, question.qclass);
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            self.evict_least_recently_used();
+        }
+
+        self.clock += 1;
+        let authorities = if response.answers.is_empty() {
+            response.authorities.iter().filter(|record| record.rtype == DNS_TYPE_SOA).cloned().collect()
+        } else {
+            Vec::new()
+        };
+        self.entries.insert(key, CacheEntry {
+            rcode,
+            answers: response.answers.clone(),
+            authorities,
+            stored: now,
+            expires: now + Duration::from_secs(ttl as u64),
+            last_used: self.clock,
+        });
+        true
+    }
+
+    /// Compteurs de succès et d'échecs
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+        }
+    }
+
+    /// Durée de cache négatif annoncée par le SOA de la section autorité
+    fn negative_ttl(authorities: &[DnsAnswer]) -> Option<u32> {
+        authorities.iter().find_map(|record| match &record.rdata {
+            RData::Soa { minimum, .. } => Some(record.ttl.min(*minimum)),
+            _ => None,
+        })
+    }
+
+    /// Retire l'entrée utilisée le moins récemment
+    fn evict_least_recently_used(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| key.clone());
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DNS_TYPE_A;
+
+    fn question(nom: &str) -> DnsQuestion {
+        DnsQuestion::new(nom.to_string(), DNS_TYPE_A).unwrap()
+    }
+
+    fn reponse(nom: &str, ttl: u32) -> DnsMessage {
+        let requete = DnsMessage::new_query(1, nom.to_string(), DNS_TYPE_A).unwrap();
+        let mut reponse = DnsMessage::new_response(&requete);
+        reponse.add_answer(DnsAnswer::new_a_record(nom.to_string(), [10, 0, 0, 1], ttl));
+        reponse
+    }
+
+    fn reponse_negative(nom: &str, soa_ttl: u32, minimum: u32) -> DnsMessage {
+        let requete = DnsMessage::new_query(1, nom.to_string(), DNS_TYPE_A).unwrap();
+        let mut reponse = DnsMessage::new_response(&requete);
+        reponse.set_rcode(Rcode::NxDomain);
+        reponse.add_authority(DnsAnswer::new("exemple.com".to_string(), soa_ttl, RData::Soa {
+            mname: "ns1.exemple.com".to_string(),
+            rname: "admin.exemple.com".to_string(),
+            serial: 1,
+            refresh: 3600,
+            retry: 600,
+            expire: 86400,
+            minimum,
+        }));
+        reponse
+    }
+
+    #[test]
+    fn test_ttl_decroissant_et_expiration() {
+        let mut cache = DnsCache::new(10);
+        let debut = Instant::now();
+        assert!(cache.insert_at(&question("a.com"), &reponse("a.com", 60), debut));
+
+        let trouve = cache.get_at("A.COM", DNS_TYPE_A, 1, debut + Duration::from_secs(45)).unwrap();
+        assert_eq!(trouve.answers[0].ttl, 15);
+        assert!(cache.get_at("a.com", DNS_TYPE_A, 1, debut + Duration::from_secs(60)).is_none());
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1, entries: 0 });
+    }
+
+    #[test]
+    fn test_cache_negatif() {
+        let mut cache = DnsCache::new(10);
+        let debut = Instant::now();
+
+        // Durée négative limitée par le champ minimum du SOA
+        assert!(cache.insert_at(&question("x.exemple.com"), &reponse_negative("x.exemple.com", 3600, 30), debut));
+        let trouve = cache.get_at("x.exemple.com", DNS_TYPE_A, 1, debut + Duration::from_secs(10)).unwrap();
+        assert!(trouve.is_negative());
+        assert_eq!(trouve.rcode, Rcode::NxDomain);
+        assert!(cache.get_at("x.exemple.com", DNS_TYPE_A, 1, debut + Duration::from_secs(31)).is_none());
+
+        // Sans SOA, une réponse négative n'est pas mise en cache
+        let mut sans_soa = reponse_negative("y.exemple.com", 3600, 30);
+        sans_soa.authorities.clear();
+        assert!(!cache.insert_at(&question("y.exemple.com"), &sans_soa, debut));
+    }
+
+    #[test]
+    fn test_eviction_lru() {
+        let mut cache = DnsCache::new(2);
+        cache.insert(&question("a.com"), &reponse("a.com", 60));
+        cache.insert(&question("b.com"), &reponse("b.com", 60));
+
+        // a.com devient la plus récemment utilisée : b.com est évincée
+        assert!(cache.get("a.com", DNS_TYPE_A, 1).is_some());
+        cache.insert(&question("c.com"), &reponse("c.com", 60));
+
+        assert!(cache.get("b.com", DNS_TYPE_A, 1).is_none());
+        assert!(cache.get("a.com", DNS_TYPE_A, 1).is_some());
+        assert!(cache.get("c.com", DNS_TYPE_A, 1).is_some());
+        assert_eq!(cache.stats().entries, 2);
+    }
+
+    #[test]
+    fn test_cle_issue_de_la_requete() {
+        // Réponse portant une autre question : rangée sous la question de la requête
+        let mut cache = DnsCache::new(10);
+        assert!(cache.insert(&question("a.com"), &reponse("b.com", 60)));
+        assert!(cache.get("b.com", DNS_TYPE_A, 1).is_none());
+        assert!(cache.get("a.com", DNS_TYPE_A, 1).is_some());
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+/// Bloc d'adresses IP en notation CIDR (`10.0.0.0/8`, `fd00::/8`). Une adresse seule
+/// désigne un bloc d'une seule adresse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl Cidr {
+    /// Bloc de longueur `prefix_len` contenant `address` (les bits d'hôte sont mis à zéro).
+    /// La longueur est ramenée à la taille de l'adresse si elle la dépasse.
+    pub fn new(address: IpAddr, prefix_len: u8) -> Self {
+        let prefix_len = prefix_len.min(if address.is_ipv4() { 32 } else { 128 });
+        let network = match address {
+            IpAddr::V4(ip) => {
+                let masque = u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or(0);
+                IpAddr::V4(Ipv4Addr::from(u32::from(ip) & masque))
+            }
+            IpAddr::V6(ip) => {
+                let masque = u128::MAX.checked_shl(128 - prefix_len as u32).unwrap_or(0);
+                IpAddr::V6(Ipv6Addr::from(u128::from(ip) & masque))
+            }
+        };
+        Cidr { network, prefix_len }
+    }
+
+    /// Première adresse du bloc
+    pub fn network(&self) -> IpAddr {
+        self.network
+    }
+
+    /// Longueur du préfixe en bits
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Indique si `address` appartient au bloc. Une adresse IPv6 de correspondance IPv4
+    /// (`::ffff:a.b.c.d`) est comparée comme l'adresse IPv4 qu'elle représente.
+    pub fn contains(&self, address: IpAddr) -> bool {
+        let address = match address {
+            IpAddr::V6(ip) if self.network.is_ipv4() => ip.to_ipv4_mapped().map_or(address, IpAddr::V4),
+            _ => address,
+        };
+        address.is_ipv4() == self.network.is_ipv4() && Cidr::new(address, self.prefix_len).network == self.network
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = String;
+
+    fn from_str(texte: &str) -> Result<Self, Self::Err> {
+        let invalide = || format!("bloc CIDR invalide: {}", texte);
+        let (adresse, longueur) = match texte.split_once('/') {
+            Some((adresse, longueur)) => (adresse, Some(longueur)),
+            None => (texte, None),
+        };
+        let adresse: IpAddr = adresse.parse().map_err(|_| invalide())?;
+        let maximum = if adresse.is_ipv4() { 32 } else { 128 };
+        let longueur = match longueur {
+            Some(longueur) => longueur.parse::<u8>().ok().filter(|&l| l <= maximum).ok_or_else(invalide)?,
+            None => maximum,
+        };
+        Ok(Cidr::new(adresse, longueur))
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_appartenance_cidr() {
+        let reseau: Cidr = "127.0.0.0/8".parse().unwrap();
+        assert!(reseau.contains("127.4.5.6".parse().unwrap()));
+        assert!(!reseau.contains("128.0.0.1".parse().unwrap()));
+        assert!(reseau.contains("::ffff:127.0.0.1".parse().unwrap()));
+        assert!(!reseau.contains("::1".parse().unwrap()));
+
+        // Les bits d'hôte sont ignorés, une adresse seule est un bloc d'une adresse
+        assert_eq!("10.1.2.3/16".parse::<Cidr>().unwrap().to_string(), "10.1.0.0/16");
+        let hote: Cidr = "2001:db8::1".parse().unwrap();
+        assert_eq!(hote.prefix_len(), 128);
+        assert!(hote.contains("2001:db8::1".parse().unwrap()));
+        assert!("0.0.0.0/0".parse::<Cidr>().unwrap().contains("192.0.2.1".parse().unwrap()));
+
+        for invalide in ["10.0.0.0/33", "fd00::/129", "exemple.com/8", "10.0.0.0/"] {
+            assert!(invalide.parse::<Cidr>().is_err(), "{}", invalide);
+        }
+    }
+}
@@ -1,14 +1,150 @@
 use std::io::{self, Write};
-use std::net::SocketAddr;
-use std::time::Duration;
-use tokio::net::UdpSocket;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::net::{TcpStream, UdpSocket};
 use tokio::time::timeout;
-use tp7_dns::{DnsMessage, DNS_TYPE_A};
+use tp7_dns::{read_tcp_message, reverse_name, sign_request, type_from_str, type_to_string, unix_time, write_tcp_message, DnsAnswer, DnsCache, DnsError, DnsMessage, Edns, RData, Rcode, TsigContext, TsigKey, Update};
+use tp7_dns::{DNS_TYPE_A, DNS_TYPE_OPT, DNS_TYPE_PTR, DNS_TYPE_TSIG, EDNS_UDP_PAYLOAD};
+
+/// Nombre de réponses conservées dans le cache du client
+const CAPACITE_CACHE: usize = 256;
+
+/// Tours supplémentaires par défaut lorsqu'aucun serveur ne répond
+const ESSAIS_PAR_DEFAUT: u32 = 2;
+
+/// Attente par défaut d'une réponse au premier tour
+const DELAI_INITIAL_PAR_DEFAUT: Duration = Duration::from_secs(1);
+
+/// Attente maximale d'une réponse, atteinte par doublements successifs
+const DELAI_MAXIMUM: Duration = Duration::from_secs(8);
+
+/// Serveur interrogé par défaut (celui de `serveur_dns`)
+const SERVEUR_PAR_DEFAUT: &str = "127.0.0.1:8053";
+
+/// Port utilisé lorsque `@serveur` ne précise qu'une adresse
+const PORT_DNS: u16 = 53;
+
+/// Code de sortie en cas d'arguments invalides (les codes 0 à 23 reprennent le RCODE de la réponse)
+const SORTIE_USAGE: i32 = 64;
+
+/// Code de sortie lorsqu'aucune réponse n'a pu être obtenue
+const SORTIE_SANS_REPONSE: i32 = 69;
+
+/// Aide affichée en cas d'arguments invalides
+const USAGE: &str = "Usage: client_dns [@SERVEUR[:PORT]]... NOM [TYPE] [+tcp] [+norec] [+short] \
+                     [+timeout=SECONDES] [+retries=N]
+Plusieurs serveurs sont interrogés à tour de rôle. Sans argument, lance la session interactive.";
+
+/// Options du mode ligne de commande, à la manière de dig
+#[derive(Debug, Clone, PartialEq)]
+struct OptionsDig {
+    /// Serveurs interrogés à tour de rôle, dans l'ordre de la ligne de commande
+    serveurs: Vec<SocketAddr>,
+    nom: String,
+    qtype: u16,
+    /// Interroge directement en TCP
+    tcp: bool,
+    /// Positionne le bit RD de la requête
+    recursion: bool,
+    /// N'affiche que les données de la section réponse
+    court: bool,
+    /// Attente d'une réponse au premier tour, doublée à chaque nouvel essai
+    delai: Duration,
+    /// Tours supplémentaires sur l'ensemble des serveurs
+    essais: u32,
+}
+
+impl OptionsDig {
+    /// Lit les options depuis les arguments (voir `USAGE`). Une adresse IP sans type
+    /// explicite est interrogée en résolution inverse (PTR).
+    fn depuis_arguments(arguments: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut serveurs = Vec::new();
+        let mut nom = None;
+        let mut qtype = None;
+        let (mut tcp, mut recursion, mut court) = (false, true, false);
+        let mut delai = DELAI_INITIAL_PAR_DEFAUT;
+        let mut essais = ESSAIS_PAR_DEFAUT;
+
+        for argument in arguments {
+            if let Some(adresse) = argument.strip_prefix('@') {
+                serveurs.push(adresse
+                    .parse()
+                    .or_else(|_| adresse.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, PORT_DNS)))
+                    .map_err(|_| format!("adresse de serveur invalide: {}", adresse))?);
+            } else if let Some(option) = argument.strip_prefix('+') {
+                let (option, valeur) = match option.split_once('=') {
+                    Some((option, valeur)) => (option, Some(valeur)),
+                    None => (option, None),
+                };
+                let entier = || valeur.and_then(|v| v.parse::<u32>().ok()).ok_or(format!("+{} attend un entier", option));
+                match option {
+                    "tcp" => tcp = true,
+                    "notcp" => tcp = false,
+                    "rec" | "recurse" => recursion = true,
+                    "norec" | "norecurse" => recursion = false,
+                    "short" => court = true,
+                    "noshort" => court = false,
+                    "timeout" => delai = Duration::from_secs(entier()?.max(1) as u64),
+                    "retries" => essais = entier()?,
+                    _ => return Err(format!("option inconnue: {}", argument)),
+                }
+            } else if nom.is_none() {
+                nom = Some(argument);
+            } else if qtype.is_none() {
+                qtype = Some(type_from_str(&argument).ok_or(format!("type de requête inconnu: {}", argument))?);
+            } else {
+                return Err(format!("argument inattendu: {}", argument));
+            }
+        }
+
+        let nom = nom.ok_or("nom à résoudre manquant")?;
+        if serveurs.is_empty() {
+            serveurs.push(SERVEUR_PAR_DEFAUT.parse().map_err(|_| "serveur par défaut invalide")?);
+        }
+        let (nom, qtype) = match (nom.parse::<IpAddr>(), qtype) {
+            (Ok(ip), None) => (reverse_name(ip), DNS_TYPE_PTR),
+            (_, qtype) => (nom, qtype.unwrap_or(DNS_TYPE_A)),
+        };
+        Ok(OptionsDig { serveurs, nom, qtype, tcp, recursion, court, delai, essais })
+    }
+
+    /// Construit la requête correspondant aux options
+    fn requete(&self) -> Result<DnsMessage, DnsError> {
+        let mut requete = DnsMessage::new_query(rand::random::<u16>(), self.nom.clone(), self.qtype)?;
+        requete.header.set_recursion_desired(self.recursion);
+        requete.set_edns(Edns::new(EDNS_UDP_PAYLOAD));
+        Ok(requete)
+    }
+}
+
+/// Réponse obtenue par `ClientDns::echanger`
+pub struct Echange {
+    pub reponse: DnsMessage,
+    /// Taille du message reçu en octets
+    pub taille: usize,
+    /// Serveur ayant répondu
+    pub serveur: SocketAddr,
+    /// Vrai si la réponse est arrivée par TCP
+    pub tcp: bool,
+}
 
 /// Structure représentant le client DNS
 pub struct ClientDns {
     socket: UdpSocket,
-    serveur_dns: SocketAddr,
+    /// Serveurs interrogés à tour de rôle, à partir du dernier ayant répondu
+    serveurs: Vec<SocketAddr>,
+    /// Index dans `serveurs` du dernier serveur ayant répondu
+    serveur_prefere: AtomicUsize,
+    /// Tours supplémentaires lorsqu'aucun serveur n'a répondu au premier
+    essais: u32,
+    /// Attente d'une réponse au premier tour, doublée à chaque tour suivant
+    delai_initial: Duration,
+    /// Réponses déjà obtenues, réutilisées tant que leur TTL n'est pas écoulé
+    cache: Mutex<DnsCache>,
+    /// Clé TSIG signant les requêtes envoyées, si configurée
+    cle_tsig: Option<TsigKey>,
 }
 
 impl ClientDns {
@@ -17,105 +153,260 @@ impl ClientDns {
         let socket = UdpSocket::bind("0.0.0.0:0").await?; // Bind sur un port aléatoire
         let serveur_dns: SocketAddr = serveur_dns.parse()?;
         
-        println!("Client DNS connecté au serveur {}", serveur_dns);
-        
         Ok(ClientDns {
             socket,
-            serveur_dns,
+            serveurs: vec![serveur_dns],
+            serveur_prefere: AtomicUsize::new(0),
+            essais: ESSAIS_PAR_DEFAUT,
+            delai_initial: DELAI_INITIAL_PAR_DEFAUT,
+            cache: Mutex::new(DnsCache::new(CAPACITE_CACHE)),
+            cle_tsig: None,
         })
     }
 
+    /// Ajoute un serveur interrogé lorsque les précédents ne répondent pas
+    pub fn avec_serveur(mut self, serveur: SocketAddr) -> Self {
+        if !self.serveurs.contains(&serveur) {
+            self.serveurs.push(serveur);
+        }
+        self
+    }
+
+    /// Règle les nouveaux essais : `essais` tours supplémentaires sur l'ensemble des serveurs,
+    /// avec une attente de `delai_initial` au premier tour puis doublée à chaque tour
+    pub fn avec_essais(mut self, essais: u32, delai_initial: Duration) -> Self {
+        self.essais = essais;
+        self.delai_initial = delai_initial;
+        self
+    }
+
+    /// Signe les requêtes avec `cle` (TSIG) ; les réponses non signées par le serveur sont rejetées
+    pub fn avec_tsig(mut self, cle: TsigKey) -> Self {
+        println!("Requêtes signées avec la clé TSIG {}", cle.name);
+        self.cle_tsig = Some(cle);
+        self
+    }
+
     /// Résout un nom de domaine en adresse IP
     pub async fn resoudre_domaine(&self, domaine: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
-        println!("\nRésolution de '{}'...", domaine);
+        let donnees = self.resoudre(domaine, DNS_TYPE_A).await?;
+        
+        // Prend la première réponse de type A
+        Ok(donnees.and_then(|donnees| {
+            donnees.into_iter().find_map(|rdata| match rdata {
+                RData::A(ip) => Some(ip.to_string()),
+                _ => None,
+            })
+        }))
+    }
+
+    /// Résolution inverse : nom associé à une adresse IP (premier PTR)
+    pub async fn resoudre_inverse(&self, ip: IpAddr) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let donnees = self.resoudre(&reverse_name(ip), DNS_TYPE_PTR).await?;
+        Ok(donnees.and_then(|donnees| {
+            donnees.into_iter().find_map(|rdata| match rdata {
+                RData::Ptr(nom) => Some(nom),
+                _ => None,
+            })
+        }))
+    }
+
+    /// Résout un nom de domaine pour un type d'enregistrement donné
+    pub async fn resoudre(&self, domaine: &str, qtype: u16) -> Result<Option<Vec<RData>>, Box<dyn std::error::Error>> {
+        println!("\nRésolution de '{}' (type {})...", domaine, type_to_string(qtype));
         
         // Génère un ID unique pour la requête
         let id_requete = rand::random::<u16>();
         
-        // Crée la requête DNS
-        let requete = DnsMessage::new_query(id_requete, domaine.to_string(), DNS_TYPE_A);
-        let requete_bytes = requete.to_bytes();
-        
-        println!("Envoi de la requête (ID: {}, {} bytes)", id_requete, requete_bytes.len());
+        // Crée la requête DNS en annonçant la taille UDP acceptée (EDNS(0))
+        let mut requete = DnsMessage::new_query(id_requete, domaine.to_string(), qtype)?;
+        requete.set_edns(Edns::new(EDNS_UDP_PAYLOAD));
         
-        // Envoie la requête
-        self.socket.send_to(&requete_bytes, self.serveur_dns).await?;
+        // Réutilise une réponse encore valide si possible
+        let en_cache = self.cache.lock().unwrap().get(&requete.questions[0].qname, qtype, 1);
+        if let Some(en_cache) = en_cache {
+            println!("Réponse trouvée dans le cache");
+            return Ok(Self::interpreter_reponse(en_cache.to_response(&requete)));
+        }
         
-        // Attend la réponse avec timeout
-        let mut buffer = vec![0u8; 512];
+        println!("Envoi de la requête (ID: {})", id_requete);
         
-        match timeout(Duration::from_secs(5), self.socket.recv_from(&mut buffer)).await {
-            Ok(Ok((taille, adresse))) => {
-                println!("Réponse reçue de {} ({} bytes)", adresse, taille);
-                
-                // Parse la réponse
-                match DnsMessage::from_bytes(&buffer[..taille]) {
-                    Ok(reponse) => {
-                        // Vérifie que c'est bien une réponse à notre requête
-                        if reponse.header.id != id_requete {
-                            println!("ATTENTION: ID de réponse incorrect (attendu: {}, reçu: {})", 
-                                id_requete, reponse.header.id);
-                            return Ok(None);
-                        }
-                        
-                        // Vérifie le code de réponse
-                        let rcode = reponse.header.flags & 0x000F;
-                        match rcode {
-                            0 => {
-                                // Succès - parse les réponses
-                                if reponse.answers.is_empty() {
-                                    println!("Aucune réponse trouvée");
-                                    Ok(None)
-                                } else {
-                                    // Prend la première réponse de type A
-                                    for answer in &reponse.answers {
-                                        if answer.rtype == DNS_TYPE_A && answer.rdata.len() == 4 {
-                                            let ip = format!("{}.{}.{}.{}", 
-                                                answer.rdata[0], answer.rdata[1], 
-                                                answer.rdata[2], answer.rdata[3]);
-                                            println!("{} -> {} (TTL: {}s)", domaine, ip, answer.ttl);
-                                            return Ok(Some(ip));
-                                        }
-                                    }
-                                    println!("Aucune réponse IPv4 trouvée");
-                                    Ok(None)
-                                }
-                            }
-                            3 => {
-                                println!("Domaine inexistant (NXDOMAIN)");
-                                Ok(None)
-                            }
-                            4 => {
-                                println!("Type de requête non supporté par le serveur");
-                                Ok(None)
-                            }
-                            _ => {
-                                println!("Erreur serveur DNS (code: {})", rcode);
-                                Ok(None)
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Erreur parsing réponse DNS: {}", e);
-                        Ok(None)
+        // Envoie la requête, avec nouveaux essais et bascule entre les serveurs
+        match self.echanger(&requete, false).await? {
+            Some(echange) => {
+                println!("Réponse reçue de {} ({} bytes{})", echange.serveur, echange.taille,
+                    if echange.tcp { ", reprise en TCP après troncature" } else { "" });
+                self.cache.lock().unwrap().insert(&requete.questions[0], &echange.reponse);
+                Ok(Self::interpreter_reponse(echange.reponse))
+            }
+            None => {
+                println!("Timeout - Pas de réponse du serveur DNS");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Envoie une requête telle quelle, sans passer par le cache ni rien afficher, et retourne
+    /// `None` si aucun serveur n'a répondu. À chaque tour, les serveurs sont interrogés l'un
+    /// après l'autre à partir du dernier ayant répondu, avec une attente doublée d'un tour à
+    /// l'autre. Une réponse UDP tronquée est reprise en TCP auprès du même serveur.
+    pub async fn echanger(&self, requete: &DnsMessage, tcp: bool) -> Result<Option<Echange>, Box<dyn std::error::Error>> {
+        let mut buffer = vec![0u8; EDNS_UDP_PAYLOAD as usize];
+        // Signature de chaque envoi, pour accepter la réponse tardive à un envoi précédent
+        let mut signatures = Vec::new();
+        for tour in 0..=self.essais {
+            let delai = self.delai_tour(tour);
+            for serveur in self.ordre_serveurs() {
+                let (requete_bytes, mut signature) = self.encoder_requete(requete)?;
+                if tcp {
+                    // Serveur injoignable, muet, ou réponse illisible ou mal signée : on passe au suivant
+                    let Ok(Ok(donnees)) = timeout(delai, self.echanger_tcp(serveur, &requete_bytes)).await else {
+                        continue;
+                    };
+                    let Ok(reponse) = decoder_reponse(requete, &donnees, signature.as_mut()) else {
+                        continue;
+                    };
+                    self.retenir(serveur);
+                    return Ok(Some(Echange { reponse, taille: donnees.len(), serveur, tcp: true }));
+                }
+
+                self.socket.send_to(&requete_bytes, serveur).await?;
+                signatures.push(signature);
+                let limite = Instant::now() + delai;
+                let recue = loop {
+                    let restant = limite.saturating_duration_since(Instant::now());
+                    let Ok(recue) = timeout(restant, self.recevoir_udp(requete.header.id, &mut buffer)).await else {
+                        break None;
+                    };
+                    // La réponse peut venir d'un serveur interrogé plus tôt dans l'échange ; un
+                    // datagramme illisible ou mal signé est ignoré comme un datagramme parasite.
+                    // Chaque vérification part d'une copie de la signature, laissée intacte en cas d'échec.
+                    let (taille, source) = recue?;
+                    let reponse = signatures
+                        .iter()
+                        .find_map(|signature| decoder_reponse(requete, &buffer[..taille], signature.clone().as_mut()).ok());
+                    if let Some(reponse) = reponse {
+                        break Some((reponse, taille, source));
                     }
+                };
+                let Some((reponse, taille, serveur)) = recue else {
+                    continue;
+                };
+                self.retenir(serveur);
+                if !reponse.header.truncated() {
+                    return Ok(Some(Echange { reponse, taille, serveur, tcp: false }));
                 }
+
+                // Réponse tronquée : la requête est reprise en TCP auprès du même serveur,
+                // puis auprès du suivant si celui-ci ne donne pas de réponse valable
+                let (requete_bytes, mut signature) = self.encoder_requete(requete)?;
+                let Ok(Ok(donnees)) = timeout(delai, self.echanger_tcp(serveur, &requete_bytes)).await else {
+                    continue;
+                };
+                let Ok(reponse) = decoder_reponse(requete, &donnees, signature.as_mut()) else {
+                    continue;
+                };
+                return Ok(Some(Echange { reponse, taille: donnees.len(), serveur, tcp: true }));
             }
-            Ok(Err(e)) => {
-                eprintln!("Erreur réception: {}", e);
-                Err(Box::new(e))
+        }
+        Ok(None)
+    }
+
+    /// Attente d'une réponse au tour `tour` : délai initial doublé à chaque tour,
+    /// plafonné à `DELAI_MAXIMUM` (sauf si le délai initial le dépasse déjà)
+    fn delai_tour(&self, tour: u32) -> Duration {
+        let facteur = 1u32.checked_shl(tour).unwrap_or(u32::MAX);
+        self.delai_initial.saturating_mul(facteur).min(DELAI_MAXIMUM.max(self.delai_initial))
+    }
+
+    /// Serveurs dans l'ordre d'interrogation, en commençant par le dernier ayant répondu
+    fn ordre_serveurs(&self) -> Vec<SocketAddr> {
+        let premier = self.serveur_prefere.load(Ordering::Relaxed);
+        (0..self.serveurs.len()).map(|i| self.serveurs[(premier + i) % self.serveurs.len()]).collect()
+    }
+
+    /// Retient le serveur ayant répondu pour les prochaines requêtes
+    fn retenir(&self, serveur: SocketAddr) {
+        if let Some(index) = self.serveurs.iter().position(|&s| s == serveur) {
+            self.serveur_prefere.store(index, Ordering::Relaxed);
+        }
+    }
+
+    /// Échange un message avec `serveur` sur une nouvelle connexion TCP
+    async fn echanger_tcp(&self, serveur: SocketAddr, requete_bytes: &[u8]) -> io::Result<Vec<u8>> {
+        let mut flux = TcpStream::connect(serveur).await?;
+        write_tcp_message(&mut flux, requete_bytes).await?;
+        read_tcp_message(&mut flux).await
+    }
+
+    /// Attend le datagramme d'un des serveurs portant l'ID `id` et retourne sa taille et sa
+    /// source ; les datagrammes d'une autre source ou d'un autre échange sont ignorés
+    async fn recevoir_udp(&self, id: u16, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        loop {
+            let (taille, adresse) = self.socket.recv_from(buffer).await?;
+            if self.serveurs.contains(&adresse) && DnsMessage::from_bytes(&buffer[..taille]).map(|r| r.header.id) == Ok(id) {
+                return Ok((taille, adresse));
             }
-            Err(_) => {
-                println!("Timeout - Pas de réponse du serveur DNS");
-                Ok(None)
+        }
+    }
+
+    /// Envoie une mise à jour dynamique (RFC 2136) et retourne le code de réponse du serveur
+    pub async fn mettre_a_jour(&self, maj: &Update) -> Result<Rcode, Box<dyn std::error::Error>> {
+        let requete = maj.to_message(rand::random::<u16>());
+        let echange = self.echanger(&requete, false).await?.ok_or("pas de réponse du serveur DNS")?;
+        
+        let rcode = echange.reponse.rcode();
+        println!("Mise à jour de la zone {}: {} ({})", maj.zone, rcode, rcode.description());
+        Ok(rcode)
+    }
+
+    /// Encode une requête, signée si une clé TSIG est configurée
+    fn encoder_requete(&self, requete: &DnsMessage) -> Result<(Vec<u8>, Option<TsigContext>), DnsError> {
+        match &self.cle_tsig {
+            Some(cle) => {
+                let (octets, signature) = sign_request(&requete.to_bytes(), cle, unix_time())?;
+                Ok((octets, Some(signature)))
             }
+            None => Ok((requete.to_bytes(), None)),
+        }
+    }
+
+    /// Affiche une réponse et en extrait les données selon le code de réponse
+    fn interpreter_reponse(reponse: DnsMessage) -> Option<Vec<RData>> {
+        // Affiche les sections autorité et additionnelle éventuelles
+        afficher_section("Autorité", &reponse.authorities);
+        afficher_section("Additionnel", &reponse.additionals);
+        if let Some(edns) = reponse.edns() {
+            println!("EDNS: version {}, taille UDP {} octets", edns.version, edns.udp_payload_size);
+        }
+
+        // Vérifie le code de réponse (y compris les bits étendus EDNS)
+        let rcode = reponse.rcode();
+        println!("Statut: {} ({}){}", rcode, rcode.description(),
+            if reponse.header.authoritative() { ", réponse faisant autorité" } else { "" });
+        match rcode {
+            Rcode::NoError => {
+                // Succès - parse les réponses
+                if reponse.answers.is_empty() {
+                    println!("Aucune réponse trouvée");
+                    None
+                } else {
+                    for answer in &reponse.answers {
+                        println!("{} -> {} {} (TTL: {}s)", answer.name,
+                            type_to_string(answer.rtype), answer.rdata, answer.ttl);
+                    }
+                    Some(reponse.answers.into_iter().map(|answer| answer.rdata).collect())
+                }
+            }
+            _ => None,
         }
     }
 
     /// Lance une session interactive pour résoudre des domaines
     pub async fn session_interactive(&self) -> Result<(), Box<dyn std::error::Error>> {
         println!("\n=== Client DNS Interactif ===");
-        println!("Tapez un nom de domaine à résoudre, ou 'quit' pour quitter.");
+        println!("Tapez un nom de domaine à résoudre, suivi d'un type optionnel (A, AAAA, MX, TXT, SRV...), ou 'quit' pour quitter.");
+        println!("Une adresse IP déclenche une résolution inverse (PTR).");
         println!("Exemples de domaines configurés: exemple.com, test.local, serveur.esgi\n");
         
         loop {
@@ -133,14 +424,39 @@ impl ClientDns {
             }
             
             if domaine.eq_ignore_ascii_case("quit") || domaine.eq_ignore_ascii_case("exit") {
+                let stats = self.cache.lock().unwrap().stats();
+                println!("Cache: {} succès, {} échecs, {} entrées", stats.hits, stats.misses, stats.entries);
                 println!("Au revoir !");
                 break;
             }
             
+            // Sépare le domaine du type demandé (A par défaut)
+            let mut morceaux = domaine.split_whitespace();
+            let domaine = morceaux.next().unwrap_or_default();
+            let qtype = match morceaux.next() {
+                Some(nom_type) => match type_from_str(nom_type) {
+                    Some(qtype) => qtype,
+                    None => {
+                        println!("Type de requête inconnu: {}", nom_type);
+                        continue;
+                    }
+                },
+                None => DNS_TYPE_A,
+            };
+            
+            // Une adresse IP donne lieu à une résolution inverse (PTR)
+            let (domaine, qtype) = match domaine.parse::<IpAddr>() {
+                Ok(ip) => (reverse_name(ip), DNS_TYPE_PTR),
+                Err(_) => (domaine.to_string(), qtype),
+            };
+            let domaine = domaine.as_str();
+            
             // Résout le domaine
-            match self.resoudre_domaine(domaine).await {
-                Ok(Some(ip)) => {
-                    println!("Résolution réussie: {} -> {}", domaine, ip);
+            match self.resoudre(domaine, qtype).await {
+                Ok(Some(donnees)) => {
+                    for rdata in donnees {
+                        println!("Résolution réussie: {} -> {}", domaine, rdata);
+                    }
                 }
                 Ok(None) => {
                     println!("Impossible de résoudre '{}'", domaine);
@@ -155,18 +471,166 @@ impl ClientDns {
     }
 }
 
+/// Affiche les enregistrements d'une section de la réponse
+fn afficher_section(titre: &str, records: &[DnsAnswer]) {
+    // Le pseudo-enregistrement OPT est affiché à part
+    for record in records.iter().filter(|record| record.rtype != DNS_TYPE_OPT) {
+        println!("{}: {} {} {} (TTL: {}s)",
+            titre, record.name, type_to_string(record.rtype), record.rdata, record.ttl);
+    }
+}
+
+/// Vérifie la signature TSIG d'une réponse lorsque la requête était signée
+fn verifier_signature(signature: Option<&mut TsigContext>, reponse: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(signature) = signature {
+        signature.verify_response(reponse, unix_time())
+            .map_err(|e| format!("signature TSIG de la réponse refusée: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Vérifie la signature d'une réponse, la décode sans son TSIG et contrôle son ID et sa question
+fn decoder_reponse(requete: &DnsMessage, donnees: &[u8], signature: Option<&mut TsigContext>) -> Result<DnsMessage, Box<dyn std::error::Error>> {
+    verifier_signature(signature, donnees)?;
+    let reponse = sans_tsig(DnsMessage::from_bytes(donnees)?);
+    if reponse.header.id != requete.header.id {
+        return Err("ID de réponse incorrect".into());
+    }
+    if !reponse.same_questions(requete) {
+        return Err("question de la réponse différente de la requête".into());
+    }
+    Ok(reponse)
+}
+
+/// Retire l'enregistrement TSIG de la réponse, une fois vérifié, pour ne pas le mettre en cache
+fn sans_tsig(mut reponse: DnsMessage) -> DnsMessage {
+    reponse.additionals.retain(|record| record.rtype != DNS_TYPE_TSIG);
+    reponse
+}
+
 /// Fonction utilitaire pour afficher un prompt
 fn print(msg: &str) -> io::Result<()> {
     print!("{}", msg);
     io::stdout().flush()
 }
 
+/// Réponse complète au format de dig : en-tête, pseudo-section EDNS et sections du message
+fn formater_dig(options: &OptionsDig, echange: &Echange, duree: Duration) -> String {
+    let reponse = &echange.reponse;
+    let en_tete = &reponse.header;
+    let mut texte = String::new();
+    let serveurs: Vec<String> = options.serveurs.iter().map(|serveur| format!("@{}", serveur)).collect();
+    texte.push_str(&format!("; <<>> client_dns <<>> {} {} {}\n", serveurs.join(" "), options.nom, type_to_string(options.qtype)));
+    texte.push_str(&format!(";; ->>HEADER<<- opcode: {}, status: {}, id: {}\n", en_tete.opcode(), reponse.rcode(), en_tete.id));
+
+    let drapeaux: Vec<&str> = [
+        (en_tete.is_response(), "qr"),
+        (en_tete.authoritative(), "aa"),
+        (en_tete.truncated(), "tc"),
+        (en_tete.recursion_desired(), "rd"),
+        (en_tete.recursion_available(), "ra"),
+        (en_tete.authentic_data(), "ad"),
+        (en_tete.checking_disabled(), "cd"),
+    ]
+    .into_iter()
+    .filter_map(|(actif, nom)| actif.then_some(nom))
+    .collect();
+    texte.push_str(&format!(";; flags: {}; QUERY: {}, ANSWER: {}, AUTHORITY: {}, ADDITIONAL: {}\n",
+        drapeaux.join(" "), reponse.questions.len(), reponse.answers.len(), reponse.authorities.len(), reponse.additionals.len()));
+
+    if let Some(edns) = reponse.edns() {
+        texte.push_str("\n;; OPT PSEUDOSECTION:\n");
+        texte.push_str(&format!("; EDNS: version: {}, flags:{}; udp: {}\n",
+            edns.version, if edns.dnssec_ok { " do" } else { "" }, edns.udp_payload_size));
+    }
+
+    texte.push_str("\n;; QUESTION SECTION:\n");
+    for question in &reponse.questions {
+        let classe = if question.qclass == 1 { "IN".to_string() } else { format!("CLASS{}", question.qclass) };
+        let nom = if question.qname.ends_with('.') { question.qname.clone() } else { format!("{}.", question.qname) };
+        texte.push_str(&format!(";{} {} {}\n", nom, classe, type_to_string(question.qtype)));
+    }
+    for (titre, records) in [("ANSWER", &reponse.answers), ("AUTHORITY", &reponse.authorities), ("ADDITIONAL", &reponse.additionals)] {
+        // Le pseudo-enregistrement OPT est affiché dans sa propre section
+        let records: Vec<&DnsAnswer> = records.iter().filter(|record| record.rtype != DNS_TYPE_OPT).collect();
+        if !records.is_empty() {
+            texte.push_str(&format!("\n;; {} SECTION:\n", titre));
+            for record in records {
+                texte.push_str(&format!("{}\n", record));
+            }
+        }
+    }
+
+    texte.push_str(&format!("\n;; Query time: {} msec\n", duree.as_millis()));
+    texte.push_str(&format!(";; SERVER: {}#{}({})\n", echange.serveur.ip(), echange.serveur.port(), if echange.tcp { "TCP" } else { "UDP" }));
+    texte.push_str(&format!(";; MSG SIZE  rcvd: {}\n", echange.taille));
+    texte
+}
+
+/// Réponse réduite aux données de la section réponse, une par ligne (`+short`)
+fn formater_court(reponse: &DnsMessage) -> String {
+    reponse.answers.iter().map(|answer| format!("{}\n", answer.rdata)).collect()
+}
+
+/// Code de sortie correspondant au RCODE de la réponse
+fn code_sortie(rcode: Rcode) -> i32 {
+    i32::from(u16::from(rcode)).min(SORTIE_USAGE - 1)
+}
+
+/// Mode ligne de commande : une requête, la réponse affichée, et le code de sortie retourné
+async fn ligne_de_commande(arguments: Vec<String>) -> i32 {
+    let (options, requete) = match OptionsDig::depuis_arguments(arguments.into_iter())
+        .and_then(|options| options.requete().map(|requete| (options, requete)).map_err(|e| e.to_string()))
+    {
+        Ok(resultat) => resultat,
+        Err(e) => {
+            eprintln!("Erreur: {}", e);
+            eprintln!("{}", USAGE);
+            return SORTIE_USAGE;
+        }
+    };
+
+    let debut = Instant::now();
+    let echange = match ClientDns::new(&options.serveurs[0].to_string()).await {
+        Ok(client) => {
+            let client = options.serveurs[1..].iter().fold(client, |client, &serveur| client.avec_serveur(serveur));
+            client.avec_essais(options.essais, options.delai).echanger(&requete, options.tcp).await
+        }
+        Err(e) => Err(e),
+    };
+    match echange {
+        Ok(Some(echange)) => {
+            if options.court {
+                print!("{}", formater_court(&echange.reponse));
+            } else {
+                print!("{}", formater_dig(&options, &echange, debut.elapsed()));
+            }
+            code_sortie(echange.reponse.rcode())
+        }
+        Ok(None) => {
+            eprintln!(";; pas de réponse après {} essai(s) par serveur", options.essais + 1);
+            SORTIE_SANS_REPONSE
+        }
+        Err(e) => {
+            eprintln!(";; échec de la requête: {}", e);
+            SORTIE_SANS_REPONSE
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Avec des arguments : une seule requête, à la manière de dig
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    if !arguments.is_empty() {
+        std::process::exit(ligne_de_commande(arguments).await);
+    }
+    
     println!("Démarrage du client DNS...");
     
     // Se connecte au serveur DNS local
-    let client = ClientDns::new("127.0.0.1:8053").await?;
+    let client = ClientDns::new(SERVEUR_PAR_DEFAUT).await?;
+    println!("Client DNS connecté au serveur {}", SERVEUR_PAR_DEFAUT);
     
     // Teste quelques résolutions automatiques
     println!("\n=== Tests automatiques ===");
@@ -184,8 +648,280 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         tokio::time::sleep(Duration::from_millis(500)).await; // Pause entre les requêtes
     }
     
+    // Résolution inverse (le serveur doit être lancé avec --ptr)
+    let _ = client.resoudre_inverse("192.168.1.100".parse()?).await;
+    
     // Lance la session interactive
     client.session_interactive().await?;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+    use tp7_dns::{verify_request, TsigKeyring, DNS_TYPE_TXT};
+
+    /// Enregistrement TXT trop grand pour une réponse UDP
+    fn texte_long() -> RData {
+        RData::Txt(vec![vec![b'x'; 250], vec![b'y'; 250], vec![b'z'; 100]])
+    }
+
+    #[tokio::test]
+    async fn test_nouvel_essai_tcp_si_tronque() {
+        // Faux serveur : réponse UDP tronquée, réponse complète en TCP sur le même port
+        let udp = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let adresse = udp.local_addr().unwrap();
+        let tcp = TcpListener::bind(adresse).await.unwrap();
+
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; EDNS_UDP_PAYLOAD as usize];
+            let (taille, client) = udp.recv_from(&mut buffer).await.unwrap();
+            let requete = DnsMessage::from_bytes(&buffer[..taille]).unwrap();
+            let mut reponse = DnsMessage::new_response(&requete);
+            reponse.header.set_truncated(true);
+            udp.send_to(&reponse.to_bytes(), client).await.unwrap();
+
+            let (mut flux, _) = tcp.accept().await.unwrap();
+            let requete = DnsMessage::from_bytes(&read_tcp_message(&mut flux).await.unwrap()).unwrap();
+            let mut reponse = DnsMessage::new_response(&requete);
+            reponse.add_answer(DnsAnswer::new("gros.com".to_string(), 60, texte_long()));
+            write_tcp_message(&mut flux, &reponse.to_bytes()).await.unwrap();
+        });
+
+        let client = ClientDns::new(&adresse.to_string()).await.unwrap();
+        let donnees = client.resoudre("gros.com", DNS_TYPE_TXT).await.unwrap().unwrap();
+        assert_eq!(donnees, vec![texte_long()]);
+    }
+
+    #[tokio::test]
+    async fn test_mise_a_jour_signee() {
+        let cle = TsigKey::new("maj", b"secret partage".to_vec()).unwrap();
+        let mut trousseau = TsigKeyring::new();
+        trousseau.insert(cle.clone());
+
+        // Faux serveur : vérifie la signature de la requête et signe sa réponse
+        let udp = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let adresse = udp.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; EDNS_UDP_PAYLOAD as usize];
+            let (taille, client) = udp.recv_from(&mut buffer).await.unwrap();
+            let mut signature = verify_request(&buffer[..taille], &trousseau, unix_time()).unwrap().unwrap();
+            let requete = DnsMessage::from_bytes(&buffer[..taille]).unwrap();
+            let reponse = signature.sign_response(&DnsMessage::new_response(&requete).to_bytes(), unix_time());
+            udp.send_to(&reponse, client).await.unwrap();
+        });
+
+        let client = ClientDns::new(&adresse.to_string()).await.unwrap().avec_tsig(cle);
+        let maj = Update::new("exemple.com").unwrap().delete_name("www.exemple.com");
+        assert_eq!(client.mettre_a_jour(&maj).await.unwrap(), Rcode::NoError);
+    }
+
+    #[test]
+    fn test_options_ligne_de_commande() {
+        let arguments = |texte: &str| texte.split_whitespace().map(String::from).collect::<Vec<_>>().into_iter();
+
+        let options = OptionsDig::depuis_arguments(arguments("@10.0.0.1:5353 exemple.com MX +tcp +norec +short +timeout=2 +retries=0")).unwrap();
+        assert_eq!(options, OptionsDig {
+            serveurs: vec!["10.0.0.1:5353".parse().unwrap()],
+            nom: "exemple.com".to_string(),
+            qtype: tp7_dns::DNS_TYPE_MX,
+            tcp: true,
+            recursion: false,
+            court: true,
+            delai: Duration::from_secs(2),
+            essais: 0,
+        });
+        assert!(!options.requete().unwrap().header.recursion_desired());
+
+        // Valeurs par défaut, port 53 implicite et résolution inverse d'une adresse
+        let options = OptionsDig::depuis_arguments(arguments("@192.0.2.53 @192.0.2.54:5353 192.168.1.100")).unwrap();
+        assert_eq!(options.serveurs, vec!["192.0.2.53:53".parse().unwrap(), "192.0.2.54:5353".parse().unwrap()]);
+        assert_eq!((options.nom.as_str(), options.qtype), ("100.1.168.192.in-addr.arpa", DNS_TYPE_PTR));
+        assert!(options.recursion && !options.tcp && options.requete().unwrap().header.recursion_desired());
+
+        for invalide in ["", "exemple.com BIDULE", "exemple.com +inconnu", "exemple.com +timeout=x", "@serveur exemple.com", "a.com A b.com"] {
+            assert!(OptionsDig::depuis_arguments(arguments(invalide)).is_err(), "{}", invalide);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sortie_format_dig() {
+        // Faux serveur : NXDOMAIN faisant autorité, avec le SOA en autorité
+        let udp = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let adresse = udp.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; EDNS_UDP_PAYLOAD as usize];
+            let (taille, client) = udp.recv_from(&mut buffer).await.unwrap();
+            let requete = DnsMessage::from_bytes(&buffer[..taille]).unwrap();
+            let mut reponse = DnsMessage::new_response(&requete);
+            reponse.header.set_authoritative(true);
+            reponse.set_rcode(Rcode::NxDomain);
+            reponse.add_authority(DnsAnswer::new("exemple.com".to_string(), 300, RData::Ns("ns1.exemple.com".to_string())));
+            reponse.set_edns(Edns::new(EDNS_UDP_PAYLOAD));
+            udp.send_to(&reponse.to_bytes(), client).await.unwrap();
+        });
+
+        let arguments = vec![format!("@{}", adresse), "absent.exemple.com".to_string()];
+        let options = OptionsDig::depuis_arguments(arguments.into_iter()).unwrap();
+        let client = ClientDns::new(&adresse.to_string()).await.unwrap().avec_essais(0, options.delai);
+        let echange = client.echanger(&options.requete().unwrap(), false).await.unwrap().unwrap();
+        assert_eq!(code_sortie(echange.reponse.rcode()), 3);
+
+        let texte = formater_dig(&options, &echange, Duration::from_millis(4));
+        for ligne in [
+            ";; flags: qr aa rd; QUERY: 1, ANSWER: 0, AUTHORITY: 1, ADDITIONAL: 1",
+            "; EDNS: version: 0, flags:; udp: 4096",
+            ";absent.exemple.com. IN A",
+            ";; AUTHORITY SECTION:",
+            ";; Query time: 4 msec",
+        ] {
+            assert!(texte.lines().any(|l| l == ligne), "ligne absente: {}\n{}", ligne, texte);
+        }
+        assert!(texte.contains(";; ->>HEADER<<- opcode: QUERY, status: NXDOMAIN, id: "));
+        assert!(texte.contains(&format!(";; SERVER: 127.0.0.1#{}(UDP)", adresse.port())));
+        assert!(!texte.contains("ANSWER SECTION"));
+        assert_eq!(formater_court(&echange.reponse), "");
+    }
+
+    /// Réponse A à `requete` portant l'adresse `ip`
+    fn reponse_a(requete: &DnsMessage, ip: &str) -> Vec<u8> {
+        let mut reponse = DnsMessage::new_response(requete);
+        reponse.add_answer(DnsAnswer::new(requete.questions[0].qname.clone(), 60, RData::A(ip.parse().unwrap())));
+        reponse.to_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_nouvel_essai_et_reponses_parasites() {
+        // Faux serveur : ignore la première requête, puis envoie une réponse usurpée depuis un
+        // autre port et une réponse d'un autre échange avant la bonne réponse
+        let udp = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let adresse = udp.local_addr().unwrap();
+        let serveur = tokio::spawn(async move {
+            let intrus = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            let mut buffer = vec![0u8; EDNS_UDP_PAYLOAD as usize];
+            udp.recv_from(&mut buffer).await.unwrap();
+            let (taille, client) = udp.recv_from(&mut buffer).await.unwrap();
+            let requete = DnsMessage::from_bytes(&buffer[..taille]).unwrap();
+            intrus.send_to(&reponse_a(&requete, "6.6.6.6"), client).await.unwrap();
+            let mut autre = requete.clone();
+            autre.header.id = requete.header.id.wrapping_add(1);
+            udp.send_to(&reponse_a(&autre, "7.7.7.7"), client).await.unwrap();
+            udp.send_to(&reponse_a(&requete, "10.0.0.1"), client).await.unwrap();
+        });
+
+        let client = ClientDns::new(&adresse.to_string()).await.unwrap().avec_essais(2, Duration::from_millis(200));
+        assert_eq!(client.resoudre_domaine("perdu.com").await.unwrap(), Some("10.0.0.1".to_string()));
+        serveur.await.unwrap();
+
+        // Attente doublée à chaque tour, plafonnée
+        assert_eq!(client.delai_tour(0), Duration::from_millis(200));
+        assert_eq!(client.delai_tour(2), Duration::from_millis(800));
+        assert_eq!(client.delai_tour(40), DELAI_MAXIMUM);
+    }
+
+    #[tokio::test]
+    async fn test_bascule_entre_serveurs() {
+        // Premier serveur muet (compte les requêtes reçues), second serveur répondant toujours
+        let muet = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let adresse_muet = muet.local_addr().unwrap();
+        let recues = std::sync::Arc::new(AtomicUsize::new(0));
+        let compteur = recues.clone();
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; EDNS_UDP_PAYLOAD as usize];
+            while muet.recv_from(&mut buffer).await.is_ok() {
+                compteur.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        let actif = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let adresse_actif = actif.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; EDNS_UDP_PAYLOAD as usize];
+            while let Ok((taille, client)) = actif.recv_from(&mut buffer).await {
+                let requete = DnsMessage::from_bytes(&buffer[..taille]).unwrap();
+                actif.send_to(&reponse_a(&requete, "10.0.0.2"), client).await.unwrap();
+            }
+        });
+
+        let delai = Duration::from_millis(100);
+        let client = ClientDns::new(&adresse_muet.to_string()).await.unwrap().avec_serveur(adresse_actif).avec_essais(1, delai);
+        assert_eq!(client.resoudre_domaine("a.com").await.unwrap(), Some("10.0.0.2".to_string()));
+        assert_eq!(recues.load(Ordering::SeqCst), 1);
+
+        // Le serveur ayant répondu est interrogé en premier ensuite
+        assert_eq!(client.resoudre_domaine("b.com").await.unwrap(), Some("10.0.0.2".to_string()));
+        assert_eq!(recues.load(Ordering::SeqCst), 1);
+
+        // Aucun serveur ne répond : un envoi par tour, puis abandon
+        let seul = ClientDns::new(&adresse_muet.to_string()).await.unwrap().avec_essais(1, delai);
+        let requete = DnsMessage::new_query(1, "c.com".to_string(), DNS_TYPE_A).unwrap();
+        assert!(seul.echanger(&requete, false).await.unwrap().is_none());
+        assert_eq!(recues.load(Ordering::SeqCst), 3);
+
+        // Réponse tronquée d'un serveur injoignable en TCP : bascule sur le serveur suivant
+        let tronque = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let adresse_tronque = tronque.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; EDNS_UDP_PAYLOAD as usize];
+            while let Ok((taille, client)) = tronque.recv_from(&mut buffer).await {
+                let mut reponse = DnsMessage::new_response(&DnsMessage::from_bytes(&buffer[..taille]).unwrap());
+                reponse.header.set_truncated(true);
+                tronque.send_to(&reponse.to_bytes(), client).await.unwrap();
+            }
+        });
+        let client = ClientDns::new(&adresse_tronque.to_string()).await.unwrap().avec_serveur(adresse_actif).avec_essais(0, delai);
+        let requete = DnsMessage::new_query(2, "d.com".to_string(), DNS_TYPE_A).unwrap();
+        let echange = client.echanger(&requete, false).await.unwrap().unwrap();
+        assert_eq!((echange.serveur, echange.tcp), (adresse_actif, false));
+    }
+
+    #[tokio::test]
+    async fn test_reponse_tcp_invalide_serveur_suivant() {
+        // Premier serveur TCP : réponse sur un autre nom ; second : réponse correcte
+        let mut adresses = Vec::new();
+        for nom in ["autre.com", "tcp.com"] {
+            let ecoute = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            adresses.push(ecoute.local_addr().unwrap());
+            tokio::spawn(async move {
+                let (mut flux, _) = ecoute.accept().await.unwrap();
+                let mut requete = DnsMessage::from_bytes(&read_tcp_message(&mut flux).await.unwrap()).unwrap();
+                requete.questions[0].qname = nom.to_string();
+                write_tcp_message(&mut flux, &reponse_a(&requete, "10.0.0.3")).await.unwrap();
+            });
+        }
+
+        let client = ClientDns::new(&adresses[0].to_string()).await.unwrap().avec_serveur(adresses[1]).avec_essais(0, Duration::from_secs(2));
+        let requete = DnsMessage::new_query(4, "tcp.com".to_string(), DNS_TYPE_A).unwrap();
+        let echange = client.echanger(&requete, true).await.unwrap().unwrap();
+        assert_eq!((echange.serveur, echange.tcp), (adresses[1], true));
+    }
+
+    #[tokio::test]
+    async fn test_reponses_falsifiees_ignorees() {
+        let cle = TsigKey::new("cle", b"secret partage".to_vec()).unwrap();
+        let mut trousseau = TsigKeyring::new();
+        trousseau.insert(cle.clone());
+
+        // Faux serveur : une réponse non signée et une réponse signée par une autre clé,
+        // depuis sa propre adresse, avant la réponse correctement signée
+        let udp = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let adresse = udp.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; EDNS_UDP_PAYLOAD as usize];
+            let (taille, client) = udp.recv_from(&mut buffer).await.unwrap();
+            let mut signature = verify_request(&buffer[..taille], &trousseau, unix_time()).unwrap().unwrap();
+            let requete = DnsMessage::from_bytes(&buffer[..taille]).unwrap();
+            udp.send_to(&reponse_a(&requete, "6.6.6.6"), client).await.unwrap();
+            let autre = TsigKey::new("cle", b"autre secret".to_vec()).unwrap();
+            let (forgee, _) = sign_request(&reponse_a(&requete, "7.7.7.7"), &autre, unix_time()).unwrap();
+            udp.send_to(&forgee, client).await.unwrap();
+            udp.send_to(&signature.sign_response(&reponse_a(&requete, "10.0.0.1"), unix_time()), client).await.unwrap();
+        });
+
+        let client = ClientDns::new(&adresse.to_string()).await.unwrap().avec_tsig(cle).avec_essais(0, Duration::from_secs(2));
+        let requete = DnsMessage::new_query(9, "signe.com".to_string(), DNS_TYPE_A).unwrap();
+        let echange = client.echanger(&requete, false).await.unwrap().unwrap();
+        assert_eq!(echange.reponse.answers[0].rdata, RData::A("10.0.0.1".parse().unwrap()));
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{DnsAnswer, DnsMessage, RData, Rcode, DNS_TYPE_OPT, MAX_UDP_SIZE};
+
+/// Taille de charge utile UDP annoncée par défaut par nos clients et serveurs
+pub const EDNS_UDP_PAYLOAD: u16 = 4096;
+
+/// Informations EDNS(0) portées par le pseudo-enregistrement OPT (RFC 6891)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edns {
+    /// Taille maximale de message UDP acceptée par l'émetteur
+    pub udp_payload_size: u16,
+    /// 8 bits de poids fort du RCODE étendu
+    pub extended_rcode: u8,
+    /// Version EDNS (0 pour EDNS(0))
+    pub version: u8,
+    /// Bit DO : l'émetteur accepte les enregistrements DNSSEC
+    pub dnssec_ok: bool,
+    /// Options (code, données)
+    pub options: Vec<(u16, Vec<u8>)>,
+}
+
+impl Edns {
+    /// Crée des informations EDNS(0) annonçant la taille UDP donnée
+    pub fn new(udp_payload_size: u16) -> Self {
+        Edns {
+            udp_payload_size,
+            extended_rcode: 0,
+            version: 0,
+            dnssec_ok: false,
+            options: Vec::new(),
+        }
+    }
+
+    /// Lit les informations EDNS d'un enregistrement OPT
+    pub fn from_record(record: &DnsAnswer) -> Option<Self> {
+        if record.rtype != DNS_TYPE_OPT {
+            return None;
+        }
+
+        let mut options = Vec::new();
+        if let RData::Unknown(brut) = &record.rdata {
+            let mut pos = 0;
+            while pos + 4 <= brut.len() {
+                let code = u16::from_be_bytes([brut[pos], brut[pos + 1]]);
+                let longueur = u16::from_be_bytes([brut[pos + 2], brut[pos + 3]]) as usize;
+                if pos + 4 + longueur > brut.len() {
+                    break;
+                }
+                options.push((code, brut[pos + 4..pos + 4 + longueur].to_vec()));
+                pos += 4 + longueur;
+            }
+        }
+
+        Some(Edns {
+            udp_payload_size: record.rclass,
+            extended_rcode: (record.ttl >> 24) as u8,
+            version: (record.ttl >> 16) as u8,
+            dnssec_ok: record.ttl & 0x8000 != 0,
+            options,
+        })
+    }
+
+    /// Construit l'enregistrement OPT correspondant (nom racine, classe = taille UDP)
+    pub fn to_record(&self) -> DnsAnswer {
+        let mut brut = Vec::new();
+        for (code, donnees) in &self.options {
+            brut.extend_from_slice(&code.to_be_bytes());
+            brut.extend_from_slice(&(donnees.len() as u16).to_be_bytes());
+            brut.extend_from_slice(donnees);
+        }
+
+        let mut ttl = (self.extended_rcode as u32) << 24 | (self.version as u32) << 16;
+        if self.dnssec_ok {
+            ttl |= 0x8000;
+        }
+
+        DnsAnswer {
+            name: String::new(),
+            rtype: DNS_TYPE_OPT,
+            rclass: self.udp_payload_size,
+            ttl,
+            rdata: RData::Unknown(brut),
+        }
+    }
+}
+
+impl DnsMessage {
+    /// Informations EDNS du message, s'il contient un enregistrement OPT
+    pub fn edns(&self) -> Option<Edns> {
+        self.additionals.iter().find_map(Edns::from_record)
+    }
+
+    /// Ajoute ou remplace l'enregistrement OPT du message
+    pub fn set_edns(&mut self, edns: Edns) {
+        self.remove_edns();
+        self.add_additional(edns.to_record());
+    }
+
+    /// Retire l'enregistrement OPT du message
+    pub fn remove_edns(&mut self) {
+        self.additionals.retain(|record| record.rtype != DNS_TYPE_OPT);
+        self.header.arcount = self.additionals.len() as u16;
+    }
+
+    /// RCODE complet sur 12 bits : 4 bits de l'en-tête et 8 bits étendus de l'OPT
+    pub fn rcode(&self) -> Rcode {
+        let etendu = self.edns().map(|edns| edns.extended_rcode as u16).unwrap_or(0);
+        Rcode::from((etendu << 4) | u16::from(self.header.rcode()))
+    }
+
+    /// Remplace le RCODE complet. Un code étendu (> 15) ajoute au besoin
+    /// un enregistrement OPT pour porter ses bits de poids fort.
+    pub fn set_rcode(&mut self, rcode: Rcode) {
+        let valeur = u16::from(rcode);
+        self.header.set_rcode(rcode);
+        let edns = match self.edns() {
+            Some(edns) => Some(edns),
+            None if valeur > 0x000F => Some(Edns::new(EDNS_UDP_PAYLOAD)),
+            None => None,
+        };
+        if let Some(mut edns) = edns {
+            edns.extended_rcode = (valeur >> 4) as u8;
+            self.set_edns(edns);
+        }
+    }
+
+    /// Taille maximale d'une réponse UDP à ce message : 512 octets sans EDNS,
+    /// sinon la taille annoncée par l'émetteur, bornée par `max_local`
+    pub fn max_udp_response_size(&self, max_local: u16) -> usize {
+        match self.edns() {
+            Some(edns) => edns.udp_payload_size.min(max_local).max(MAX_UDP_SIZE as u16) as usize,
+            None => MAX_UDP_SIZE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DNS_TYPE_A;
+
+    #[test]
+    fn test_opt_aller_retour() {
+        let mut requete = DnsMessage::new_query(5, "exemple.com".to_string(), DNS_TYPE_A).unwrap();
+        let mut edns = Edns::new(1232);
+        edns.dnssec_ok = true;
+        edns.options.push((10, vec![1, 2, 3, 4, 5, 6, 7, 8]));
+        requete.set_edns(edns.clone());
+
+        let decode = DnsMessage::from_bytes(&requete.to_bytes()).unwrap();
+        assert_eq!(decode.header.arcount, 1);
+        assert_eq!(decode.edns(), Some(edns));
+        assert_eq!(decode.max_udp_response_size(EDNS_UDP_PAYLOAD), 1232);
+        assert_eq!(decode.max_udp_response_size(1000), 1000);
+    }
+
+    #[test]
+    fn test_rcode_etendu() {
+        let requete = DnsMessage::new_query(5, "exemple.com".to_string(), DNS_TYPE_A).unwrap();
+        let mut reponse = DnsMessage::new_response(&requete);
+        reponse.set_edns(Edns::new(EDNS_UDP_PAYLOAD));
+
+        // BADVERS = 16 : 0 dans l'en-tête, 1 dans les bits étendus
+        reponse.set_rcode(Rcode::BadVers);
+        let decode = DnsMessage::from_bytes(&reponse.to_bytes()).unwrap();
+        assert_eq!(decode.header.rcode(), Rcode::NoError);
+        assert_eq!(decode.edns().unwrap().extended_rcode, 1);
+        assert_eq!(decode.rcode(), Rcode::BadVers);
+
+        // Sans OPT, un code étendu en ajoute un
+        let mut sans_opt = DnsMessage::new_response(&requete);
+        sans_opt.set_rcode(Rcode::BadVers);
+        assert_eq!(sans_opt.header.arcount, 1);
+        assert_eq!(sans_opt.rcode(), Rcode::BadVers);
+    }
+
+    #[test]
+    fn test_sans_edns_limite_512() {
+        let requete = DnsMessage::new_query(5, "exemple.com".to_string(), DNS_TYPE_A).unwrap();
+        assert_eq!(requete.edns(), None);
+        assert_eq!(requete.max_udp_response_size(EDNS_UDP_PAYLOAD), MAX_UDP_SIZE);
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+
+/// Erreur de décodage (ou d'encodage) d'un message DNS
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DnsError {
+    /// Moins de 12 octets : l'en-tête lui-même est incomplet
+    TruncatedHeader,
+    /// Le message se termine au milieu d'un champ commençant à `offset`
+    UnexpectedEnd { offset: usize },
+    /// Un label annonce plus d'octets qu'il n'en reste dans le message
+    BadLabelLength { offset: usize, length: usize },
+    /// Type de label réservé (bits de poids fort 01 ou 10)
+    BadLabelType { offset: usize },
+    /// Pointeurs de compression en boucle ou trop nombreux
+    PointerLoop { offset: usize },
+    /// Label de plus de 63 octets
+    LabelTooLong { label: String },
+    /// Nom de plus de 255 octets une fois encodé
+    NameTooLong { length: usize },
+    /// Nom mal formé : label vide, caractère interdit, IDNA invalide...
+    InvalidName { name: String, reason: &'static str },
+    /// Octets en trop après le dernier enregistrement annoncé
+    TrailingBytes { count: usize },
+    /// Une section contient moins d'enregistrements que l'en-tête n'en annonce
+    CountMismatch { section: &'static str, expected: u16, found: u16 },
+    /// Données d'enregistrement incohérentes avec leur type
+    BadRdata { rtype: u16, offset: usize },
+}
+
+impl fmt::Display for DnsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DnsError::TruncatedHeader => write!(f, "en-tête incomplet (moins de 12 octets)"),
+            DnsError::UnexpectedEnd { offset } => write!(f, "message tronqué à l'octet {}", offset),
+            DnsError::BadLabelLength { offset, length } => {
+                write!(f, "label de {} octets dépassant la fin du message (octet {})", length, offset)
+            }
+            DnsError::BadLabelType { offset } => write!(f, "type de label non supporté (octet {})", offset),
+            DnsError::PointerLoop { offset } => write!(f, "boucle de pointeurs de compression (octet {})", offset),
+            DnsError::LabelTooLong { label } => write!(f, "label trop long (plus de 63 octets): {}", label),
+            DnsError::NameTooLong { length } => write!(f, "nom trop long ({} octets, 255 au maximum)", length),
+            DnsError::InvalidName { name, reason } => write!(f, "nom invalide '{}': {}", name, reason),
+            DnsError::TrailingBytes { count } => write!(f, "{} octet(s) en trop après le message", count),
+            DnsError::CountMismatch { section, expected, found } => {
+                write!(f, "section {}: {} enregistrement(s) annoncé(s), {} trouvé(s)", section, expected, found)
+            }
+            DnsError::BadRdata { rtype, offset } => {
+                write!(f, "données invalides pour le type {} (octet {})", crate::type_to_string(*rtype), offset)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DnsError {}
+
+/// Tolérance du décodage vis-à-vis des messages incomplets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Toute incohérence est une erreur
+    #[default]
+    Strict,
+    /// Les sections d'enregistrements tronquées et les octets en trop sont ignorés ;
+    /// les compteurs de l'en-tête sont ajustés à ce qui a été lu
+    Lenient,
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+
+/// Code d'opération de l'en-tête DNS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Opcode {
+    /// Requête standard
+    Query,
+    /// Requête inverse (obsolète)
+    IQuery,
+    /// État du serveur
+    Status,
+    /// Notification de changement de zone (RFC 1996)
+    Notify,
+    /// Mise à jour dynamique (RFC 2136)
+    Update,
+    /// Valeur non assignée
+    Unknown(u8),
+}
+
+impl From<u8> for Opcode {
+    fn from(valeur: u8) -> Self {
+        match valeur {
+            0 => Opcode::Query,
+            1 => Opcode::IQuery,
+            2 => Opcode::Status,
+            4 => Opcode::Notify,
+            5 => Opcode::Update,
+            autre => Opcode::Unknown(autre),
+        }
+    }
+}
+
+impl From<Opcode> for u8 {
+    fn from(opcode: Opcode) -> Self {
+        match opcode {
+            Opcode::Query => 0,
+            Opcode::IQuery => 1,
+            Opcode::Status => 2,
+            Opcode::Notify => 4,
+            Opcode::Update => 5,
+            Opcode::Unknown(valeur) => valeur,
+        }
+    }
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Opcode::Query => write!(f, "QUERY"),
+            Opcode::IQuery => write!(f, "IQUERY"),
+            Opcode::Status => write!(f, "STATUS"),
+            Opcode::Notify => write!(f, "NOTIFY"),
+            Opcode::Update => write!(f, "UPDATE"),
+            Opcode::Unknown(valeur) => write!(f, "OPCODE{}", valeur),
+        }
+    }
+}
+
+/// Code de réponse DNS, y compris les valeurs étendues portées par EDNS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rcode {
+    NoError,
+    FormErr,
+    ServFail,
+    NxDomain,
+    NotImp,
+    Refused,
+    YxDomain,
+    YxRrset,
+    NxRrset,
+    NotAuth,
+    NotZone,
+    /// Version EDNS non supportée (RFC 6891)
+    BadVers,
+    /// Valeur non assignée
+    Unknown(u16),
+}
+
+impl From<u16> for Rcode {
+    fn from(valeur: u16) -> Self {
+        match valeur {
+            0 => Rcode::NoError,
+            1 => Rcode::FormErr,
+            2 => Rcode::ServFail,
+            3 => Rcode::NxDomain,
+            4 => Rcode::NotImp,
+            5 => Rcode::Refused,
+            6 => Rcode::YxDomain,
+            7 => Rcode::YxRrset,
+            8 => Rcode::NxRrset,
+            9 => Rcode::NotAuth,
+            10 => Rcode::NotZone,
+            16 => Rcode::BadVers,
+            autre => Rcode::Unknown(autre),
+        }
+    }
+}
+
+impl From<Rcode> for u16 {
+    fn from(rcode: Rcode) -> Self {
+        match rcode {
+            Rcode::NoError => 0,
+            Rcode::FormErr => 1,
+            Rcode::ServFail => 2,
+            Rcode::NxDomain => 3,
+            Rcode::NotImp => 4,
+            Rcode::Refused => 5,
+            Rcode::YxDomain => 6,
+            Rcode::YxRrset => 7,
+            Rcode::NxRrset => 8,
+            Rcode::NotAuth => 9,
+            Rcode::NotZone => 10,
+            Rcode::BadVers => 16,
+            Rcode::Unknown(valeur) => valeur,
+        }
+    }
+}
+
+impl Rcode {
+    /// Description lisible du code de réponse
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rcode::NoError => "Succès",
+            Rcode::FormErr => "Requête mal formée",
+            Rcode::ServFail => "Échec du serveur",
+            Rcode::NxDomain => "Domaine inexistant",
+            Rcode::NotImp => "Opération non supportée par le serveur",
+            Rcode::Refused => "Requête refusée",
+            Rcode::YxDomain => "Le nom existe alors qu'il ne devrait pas",
+            Rcode::YxRrset => "L'ensemble d'enregistrements existe alors qu'il ne devrait pas",
+            Rcode::NxRrset => "L'ensemble d'enregistrements n'existe pas",
+            Rcode::NotAuth => "Serveur sans autorité sur la zone",
+            Rcode::NotZone => "Nom hors de la zone",
+            Rcode::BadVers => "Version EDNS non supportée",
+            Rcode::Unknown(_) => "Code de réponse inconnu",
+        }
+    }
+}
+
+impl fmt::Display for Rcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rcode::NoError => write!(f, "NOERROR"),
+            Rcode::FormErr => write!(f, "FORMERR"),
+            Rcode::ServFail => write!(f, "SERVFAIL"),
+            Rcode::NxDomain => write!(f, "NXDOMAIN"),
+            Rcode::NotImp => write!(f, "NOTIMP"),
+            Rcode::Refused => write!(f, "REFUSED"),
+            Rcode::YxDomain => write!(f, "YXDOMAIN"),
+            Rcode::YxRrset => write!(f, "YXRRSET"),
+            Rcode::NxRrset => write!(f, "NXRRSET"),
+            Rcode::NotAuth => write!(f, "NOTAUTH"),
+            Rcode::NotZone => write!(f, "NOTZONE"),
+            Rcode::BadVers => write!(f, "BADVERS"),
+            Rcode::Unknown(valeur) => write!(f, "RCODE{}", valeur),
+        }
+    }
+}
@@ -1,5 +1,58 @@
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::io::{Cursor};
+use std::net::IpAddr;
+
+pub mod cache;
+pub mod cidr;
+pub mod edns;
+pub mod error;
+pub mod flags;
+pub mod metrics;
+pub mod querylog;
+pub mod rdata;
+pub mod rrl;
+pub mod transport;
+pub mod tsig;
+pub mod update;
+pub mod xfr;
+pub mod zone;
+
+pub use cache::{CacheStats, CachedResponse, DnsCache};
+pub use cidr::Cidr;
+pub use edns::{Edns, EDNS_UDP_PAYLOAD};
+pub use error::{DnsError, ParseMode};
+pub use flags::{Opcode, Rcode};
+pub use metrics::{Metrics, LATENCY_BUCKETS};
+pub use querylog::{QueryLog, QueryLogEntry};
+pub use rdata::{type_from_str, type_to_string, RData};
+pub use rrl::{RateLimitConfig, RateLimitDecision, RateLimitStats, RateLimiter};
+pub use transport::{read_tcp_message, write_tcp_message, MAX_UDP_SIZE};
+pub use tsig::{sign_request, unix_time, verify_request, Tsig, TsigContext, TsigError, TsigFailure, TsigKey, TsigKeyring, TSIG_ALGORITHM};
+pub use update::Update;
+pub use xfr::{serial_newer, transfer_messages, JournalEntry, Transfer, ZoneJournal};
+pub use zone::{LookupStatus, RecordTable, Resolution, View, ZoneDiff, ZoneError};
+
+/// Nombre maximal de pointeurs de compression suivis pour un même nom
+const MAX_SAUTS_COMPRESSION: usize = 16;
+
+/// Longueur maximale d'un nom encodé, terminateur compris (RFC 1035 §2.3.4)
+const MAX_LONGUEUR_NOM: usize = 255;
+
+/// Table des suffixes déjà écrits dans un message (nom -> position) pour la compression
+type TableCompression = HashMap<String, u16>;
+
+// Masques des drapeaux de l'en-tête (RFC 1035 §4.1.1, RFC 4035 pour AD et CD)
+const FLAG_QR: u16 = 0x8000;
+const MASQUE_OPCODE: u16 = 0x7800;
+const FLAG_AA: u16 = 0x0400;
+const FLAG_TC: u16 = 0x0200;
+const FLAG_RD: u16 = 0x0100;
+const FLAG_RA: u16 = 0x0080;
+const FLAG_AD: u16 = 0x0020;
+const FLAG_CD: u16 = 0x0010;
+const MASQUE_RCODE: u16 = 0x000F;
 
 /// Structure représentant l'en-tête DNS selon RFC 1035
 #[derive(Debug, Clone)]
@@ -21,14 +74,13 @@ pub struct DnsQuestion {
 }
 
 /// Structure représentant une réponse DNS
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct DnsAnswer {
     pub name: String,     // Nom de domaine
     pub rtype: u16,       // Type d'enregistrement
     pub rclass: u16,      // Classe
     pub ttl: u32,         // Durée de vie en secondes
-    pub rdlength: u16,    // Longueur des données
-    pub rdata: Vec<u8>,   // Données de la réponse
+    pub rdata: RData,     // Données typées de la réponse
 }
 
 /// Structure principale du message DNS
@@ -37,27 +89,122 @@ pub struct DnsMessage {
     pub header: DnsHeader,
     pub questions: Vec<DnsQuestion>,
     pub answers: Vec<DnsAnswer>,
+    pub authorities: Vec<DnsAnswer>,  // Section autorité (SOA, NS)
+    pub additionals: Vec<DnsAnswer>,  // Section additionnelle (glue, etc.)
 }
 
 impl DnsHeader {
     /// Crée un nouvel en-tête DNS
     pub fn new(id: u16, is_response: bool) -> Self {
-        let mut flags = 0u16;
-        if is_response {
-            flags |= 0x8000; // Bit QR à 1 pour une réponse
-        }
-        flags |= 0x0100; // Bit RD (Recursion Desired) à 1
-        
-        DnsHeader {
+        let mut header = DnsHeader {
             id,
-            flags,
+            flags: 0,
             qdcount: 0,
             ancount: 0,
             nscount: 0,
             arcount: 0,
+        };
+        header.set_response(is_response);
+        header.set_recursion_desired(true);
+        header
+    }
+
+    fn flag(&self, masque: u16) -> bool {
+        self.flags & masque != 0
+    }
+
+    fn set_flag(&mut self, masque: u16, valeur: bool) {
+        if valeur {
+            self.flags |= masque;
+        } else {
+            self.flags &= !masque;
         }
     }
 
+    /// Bit QR : le message est une réponse
+    pub fn is_response(&self) -> bool {
+        self.flag(FLAG_QR)
+    }
+
+    pub fn set_response(&mut self, valeur: bool) {
+        self.set_flag(FLAG_QR, valeur);
+    }
+
+    /// Code d'opération (QUERY, NOTIFY, UPDATE...)
+    pub fn opcode(&self) -> Opcode {
+        Opcode::from(((self.flags & MASQUE_OPCODE) >> 11) as u8)
+    }
+
+    pub fn set_opcode(&mut self, opcode: Opcode) {
+        let valeur = (u8::from(opcode) as u16 & 0x0F) << 11;
+        self.flags = (self.flags & !MASQUE_OPCODE) | valeur;
+    }
+
+    /// Bit AA : réponse faisant autorité
+    pub fn authoritative(&self) -> bool {
+        self.flag(FLAG_AA)
+    }
+
+    pub fn set_authoritative(&mut self, valeur: bool) {
+        self.set_flag(FLAG_AA, valeur);
+    }
+
+    /// Bit TC : message tronqué
+    pub fn truncated(&self) -> bool {
+        self.flag(FLAG_TC)
+    }
+
+    pub fn set_truncated(&mut self, valeur: bool) {
+        self.set_flag(FLAG_TC, valeur);
+    }
+
+    /// Bit RD : récursion demandée
+    pub fn recursion_desired(&self) -> bool {
+        self.flag(FLAG_RD)
+    }
+
+    pub fn set_recursion_desired(&mut self, valeur: bool) {
+        self.set_flag(FLAG_RD, valeur);
+    }
+
+    /// Bit RA : récursion disponible
+    pub fn recursion_available(&self) -> bool {
+        self.flag(FLAG_RA)
+    }
+
+    pub fn set_recursion_available(&mut self, valeur: bool) {
+        self.set_flag(FLAG_RA, valeur);
+    }
+
+    /// Bit AD : données authentifiées (DNSSEC)
+    pub fn authentic_data(&self) -> bool {
+        self.flag(FLAG_AD)
+    }
+
+    pub fn set_authentic_data(&mut self, valeur: bool) {
+        self.set_flag(FLAG_AD, valeur);
+    }
+
+    /// Bit CD : vérification DNSSEC désactivée
+    pub fn checking_disabled(&self) -> bool {
+        self.flag(FLAG_CD)
+    }
+
+    pub fn set_checking_disabled(&mut self, valeur: bool) {
+        self.set_flag(FLAG_CD, valeur);
+    }
+
+    /// RCODE porté par l'en-tête (4 bits de poids faible uniquement,
+    /// voir `DnsMessage::rcode` pour le code étendu par EDNS)
+    pub fn rcode(&self) -> Rcode {
+        Rcode::from(self.flags & MASQUE_RCODE)
+    }
+
+    /// Remplace le RCODE de l'en-tête (seuls les 4 bits de poids faible sont conservés)
+    pub fn set_rcode(&mut self, rcode: Rcode) {
+        self.flags = (self.flags & !MASQUE_RCODE) | (u16::from(rcode) & MASQUE_RCODE);
+    }
+
     /// Sérialise l'en-tête DNS en bytes
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
@@ -71,73 +218,188 @@ impl DnsHeader {
     }
 
     /// Désérialise un en-tête DNS depuis des bytes
-    pub fn from_bytes(data: &[u8]) -> Result<Self, std::io::Error> {
-        let mut cursor = Cursor::new(data);
+    pub fn from_bytes(data: &[u8]) -> Result<Self, DnsError> {
+        if data.len() < 12 {
+            return Err(DnsError::TruncatedHeader);
+        }
         Ok(DnsHeader {
-            id: cursor.read_u16::<BigEndian>()?,
-            flags: cursor.read_u16::<BigEndian>()?,
-            qdcount: cursor.read_u16::<BigEndian>()?,
-            ancount: cursor.read_u16::<BigEndian>()?,
-            nscount: cursor.read_u16::<BigEndian>()?,
-            arcount: cursor.read_u16::<BigEndian>()?,
+            id: lire_u16(data, 0)?,
+            flags: lire_u16(data, 2)?,
+            qdcount: lire_u16(data, 4)?,
+            ancount: lire_u16(data, 6)?,
+            nscount: lire_u16(data, 8)?,
+            arcount: lire_u16(data, 10)?,
         })
     }
 }
 
 impl DnsQuestion {
-    /// Crée une nouvelle question DNS
-    pub fn new(domain: String, qtype: u16) -> Self {
-        DnsQuestion {
-            qname: domain,
+    /// Crée une nouvelle question DNS après validation du nom
+    pub fn new(domain: String, qtype: u16) -> Result<Self, DnsError> {
+        Ok(DnsQuestion {
+            qname: Self::validate_domain_name(&domain)?,
             qtype,
             qclass: 1, // IN (Internet)
+        })
+    }
+
+    /// Vérifie un nom de domaine et le retourne sous sa forme ASCII, sans point final.
+    /// Les noms Unicode sont convertis en punycode (IDNA) ; la casse des noms ASCII est conservée.
+    /// Chaque label fait au plus 63 octets et ne contient que des lettres, chiffres, '-' et '_',
+    /// à l'exception d'un joker '*' en premier label. Le nom encodé fait au plus 255 octets.
+    pub fn validate_domain_name(domain: &str) -> Result<String, DnsError> {
+        let domain = domain.strip_suffix('.').unwrap_or(domain);
+        let invalide = |reason| DnsError::InvalidName { name: domain.to_string(), reason };
+
+        let ascii = if domain.is_ascii() {
+            domain.to_string()
+        } else {
+            idna::domain_to_ascii(domain).map_err(|_| invalide("conversion IDNA impossible"))?
+        };
+        if ascii.is_empty() {
+            return Ok(ascii); // Racine
         }
+
+        let mut longueur = 1;
+        for (i, label) in ascii.split('.').enumerate() {
+            if label.is_empty() {
+                return Err(invalide("label vide"));
+            }
+            if label.len() > 63 {
+                return Err(DnsError::LabelTooLong { label: label.to_string() });
+            }
+            if label == "*" {
+                if i > 0 {
+                    return Err(invalide("le joker '*' doit être le premier label"));
+                }
+            } else if !label.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_') {
+                return Err(invalide("caractère non autorisé"));
+            }
+            longueur += label.len() + 1;
+        }
+        if longueur > MAX_LONGUEUR_NOM {
+            return Err(DnsError::NameTooLong { length: longueur });
+        }
+        Ok(ascii)
     }
 
-    /// Encode un nom de domaine au format DNS (avec longueurs)
-    pub fn encode_domain_name(domain: &str) -> Vec<u8> {
+    /// Encode un nom de domaine au format DNS (avec longueurs), après validation
+    pub fn encode_domain_name(domain: &str) -> Result<Vec<u8>, DnsError> {
         let mut encoded = Vec::new();
-        
-        for label in domain.split('.') {
-            if !label.is_empty() {
-                encoded.push(label.len() as u8);
-                encoded.extend_from_slice(label.as_bytes());
+        Self::write_domain_name(&Self::validate_domain_name(domain)?, &mut encoded);
+        Ok(encoded)
+    }
+
+    /// Écrit un nom déjà validé, sans compression
+    pub(crate) fn write_domain_name(domain: &str, buffer: &mut Vec<u8>) {
+        for label in labels(domain) {
+            let octets = octets_label(label);
+            buffer.push(octets.len() as u8);
+            buffer.extend_from_slice(&octets);
+        }
+        buffer.push(0); // Terminateur null
+    }
+
+    /// Encode un nom de domaine en réutilisant les suffixes déjà présents dans le message
+    /// (compression RFC 1035 §4.1.4). `buffer` doit contenir le message depuis l'en-tête
+    /// pour que les positions enregistrées soient valides.
+    pub fn encode_domain_name_compressed(domain: &str, buffer: &mut Vec<u8>, table: &mut TableCompression) {
+        let labels = labels(domain);
+
+        for i in 0..labels.len() {
+            let suffixe = labels[i..].join(".");
+            if let Some(&position) = table.get(&suffixe) {
+                buffer.write_u16::<BigEndian>(0xC000 | position).unwrap();
+                return;
+            }
+
+            // Un pointeur ne peut adresser que les 14 premiers bits du message
+            if buffer.len() < 0x4000 {
+                table.insert(suffixe, buffer.len() as u16);
             }
+
+            let octets = octets_label(labels[i]);
+            buffer.push(octets.len() as u8);
+            buffer.extend_from_slice(&octets);
         }
-        encoded.push(0); // Terminateur null
-        encoded
+        buffer.push(0); // Terminateur null
     }
 
-    /// Décode un nom de domaine depuis le format DNS
-    pub fn decode_domain_name(data: &[u8], offset: usize) -> Result<(String, usize), std::io::Error> {
+    /// Décode un nom de domaine depuis le format DNS en suivant les pointeurs de compression.
+    /// Retourne le nom et la position qui suit le nom dans le flux d'origine.
+    pub fn decode_domain_name(data: &[u8], offset: usize) -> Result<(String, usize), DnsError> {
         let mut domain = String::new();
         let mut pos = offset;
-        
+        // Position de reprise après le premier pointeur rencontré
+        let mut fin: Option<usize> = None;
+        let mut pointeurs_visites = HashSet::new();
+        // Longueur du nom encodé, terminateur compris
+        let mut longueur_totale = 1;
+
         loop {
             if pos >= data.len() {
-                return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Données insuffisantes"));
+                return Err(DnsError::UnexpectedEnd { offset: pos });
             }
-            
+
             let length = data[pos] as usize;
-            pos += 1;
-            
+
+            match length & 0xC0 {
+                0xC0 => {
+                    // Pointeur de compression sur 14 bits
+                    if pos + 1 >= data.len() {
+                        return Err(DnsError::UnexpectedEnd { offset: pos });
+                    }
+                    let cible = ((length & 0x3F) << 8) | data[pos + 1] as usize;
+
+                    if fin.is_none() {
+                        fin = Some(pos + 2);
+                    }
+                    if !pointeurs_visites.insert(cible) || pointeurs_visites.len() > MAX_SAUTS_COMPRESSION {
+                        return Err(DnsError::PointerLoop { offset: pos });
+                    }
+
+                    pos = cible;
+                    continue;
+                }
+                0x00 => {}
+                _ => {
+                    return Err(DnsError::BadLabelType { offset: pos });
+                }
+            }
+
             if length == 0 {
+                pos += 1;
                 break;
             }
-            
-            if pos + length > data.len() {
-                return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Données insuffisantes"));
+
+            if pos + 1 + length > data.len() {
+                return Err(DnsError::BadLabelLength { offset: pos, length });
+            }
+
+            longueur_totale += 1 + length;
+            if longueur_totale > MAX_LONGUEUR_NOM {
+                return Err(DnsError::NameTooLong { length: longueur_totale });
             }
-            
+
             if !domain.is_empty() {
                 domain.push('.');
             }
-            
-            domain.push_str(&String::from_utf8_lossy(&data[pos..pos + length]));
-            pos += length;
+
+            // Un '.' ou un octet non imprimable dans un label est échappé (RFC 1035 §5.1)
+            for &octet in &data[pos + 1..pos + 1 + length] {
+                match octet {
+                    b'.' | b'\\' => {
+                        domain.push('\\');
+                        domain.push(octet as char);
+                    }
+                    0x21..=0x7e => domain.push(octet as char),
+                    _ => domain.push_str(&format!("\\{:03}", octet)),
+                }
+            }
+            pos += 1 + length;
         }
-        
-        Ok((domain, pos))
+
+        Ok((domain, fin.unwrap_or(pos)))
     }
 
     /// Sérialise la question en bytes
@@ -145,8 +407,7 @@ impl DnsQuestion {
         let mut bytes = Vec::new();
         
         // Encode le nom de domaine
-        let encoded_name = Self::encode_domain_name(&self.qname);
-        bytes.extend_from_slice(&encoded_name);
+        Self::write_domain_name(&self.qname, &mut bytes);
         
         // Ajoute le type et la classe
         bytes.write_u16::<BigEndian>(self.qtype).unwrap();
@@ -154,172 +415,655 @@ impl DnsQuestion {
         
         bytes
     }
+
+    /// Indique si deux questions portent sur le même nom (sans tenir compte de la casse),
+    /// le même type et la même classe
+    pub fn matches(&self, other: &DnsQuestion) -> bool {
+        self.qname.trim_end_matches('.').eq_ignore_ascii_case(other.qname.trim_end_matches('.'))
+            && self.qtype == other.qtype
+            && self.qclass == other.qclass
+    }
+
+    /// Écrit la question dans un message en cours de construction, avec compression du nom
+    fn write_compressed(&self, buffer: &mut Vec<u8>, table: &mut TableCompression) {
+        Self::encode_domain_name_compressed(&self.qname, buffer, table);
+        buffer.write_u16::<BigEndian>(self.qtype).unwrap();
+        buffer.write_u16::<BigEndian>(self.qclass).unwrap();
+    }
 }
 
 impl DnsAnswer {
-    /// Crée une nouvelle réponse DNS pour une adresse IPv4
-    pub fn new_a_record(domain: String, ip: [u8; 4], ttl: u32) -> Self {
+    /// Crée un enregistrement de classe IN à partir de données typées
+    pub fn new(domain: String, ttl: u32, rdata: RData) -> Self {
         DnsAnswer {
             name: domain,
-            rtype: 1, // Type A
+            rtype: rdata.record_type().unwrap_or(0),
             rclass: 1, // Classe IN
             ttl,
-            rdlength: 4,
-            rdata: ip.to_vec(),
+            rdata,
         }
     }
 
+    /// Crée une nouvelle réponse DNS pour une adresse IPv4
+    pub fn new_a_record(domain: String, ip: [u8; 4], ttl: u32) -> Self {
+        Self::new(domain, ttl, RData::A(ip.into()))
+    }
+
     /// Sérialise la réponse en bytes
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
         
         // Encode le nom de domaine
-        let encoded_name = DnsQuestion::encode_domain_name(&self.name);
-        bytes.extend_from_slice(&encoded_name);
+        DnsQuestion::write_domain_name(&self.name, &mut bytes);
         
         // Ajoute type, classe, TTL et longueur des données
+        let rdata = self.rdata.to_bytes();
         bytes.write_u16::<BigEndian>(self.rtype).unwrap();
         bytes.write_u16::<BigEndian>(self.rclass).unwrap();
         bytes.write_u32::<BigEndian>(self.ttl).unwrap();
-        bytes.write_u16::<BigEndian>(self.rdlength).unwrap();
+        bytes.write_u16::<BigEndian>(rdata.len() as u16).unwrap();
         
         // Ajoute les données de la réponse
-        bytes.extend_from_slice(&self.rdata);
+        bytes.extend_from_slice(&rdata);
         
         bytes
     }
+
+    /// Écrit la réponse dans un message en cours de construction, avec compression des noms
+    fn write_compressed(&self, buffer: &mut Vec<u8>, table: &mut TableCompression) {
+        DnsQuestion::encode_domain_name_compressed(&self.name, buffer, table);
+        buffer.write_u16::<BigEndian>(self.rtype).unwrap();
+        buffer.write_u16::<BigEndian>(self.rclass).unwrap();
+        buffer.write_u32::<BigEndian>(self.ttl).unwrap();
+
+        // La longueur n'est connue qu'après l'écriture des données compressées
+        let position_longueur = buffer.len();
+        buffer.write_u16::<BigEndian>(0).unwrap();
+        self.rdata.write_compressed(buffer, table);
+        let rdlength = (buffer.len() - position_longueur - 2) as u16;
+        buffer[position_longueur..position_longueur + 2].copy_from_slice(&rdlength.to_be_bytes());
+    }
+}
+
+impl fmt::Display for DnsAnswer {
+    /// Affiche l'enregistrement au format des fichiers de zone
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let classe = match self.rclass {
+            1 => "IN".to_string(),
+            3 => "CH".to_string(),
+            4 => "HS".to_string(),
+            DNS_CLASS_NONE => "NONE".to_string(),
+            DNS_CLASS_ANY => "ANY".to_string(),
+            autre => format!("CLASS{}", autre),
+        };
+        write!(f, "{} {} {} {} {}", fqdn(&self.name), self.ttl, classe, type_to_string(self.rtype), self.rdata)
+    }
 }
 
 impl DnsMessage {
-    /// Crée une nouvelle requête DNS
-    pub fn new_query(id: u16, domain: String, qtype: u16) -> Self {
+    /// Crée une nouvelle requête DNS ; échoue si le nom demandé est invalide
+    pub fn new_query(id: u16, domain: String, qtype: u16) -> Result<Self, DnsError> {
         let mut header = DnsHeader::new(id, false);
         header.qdcount = 1;
         
-        let question = DnsQuestion::new(domain, qtype);
+        let question = DnsQuestion::new(domain, qtype)?;
         
-        DnsMessage {
+        Ok(DnsMessage {
             header,
             questions: vec![question],
             answers: vec![],
-        }
+            authorities: vec![],
+            additionals: vec![],
+        })
     }
 
     /// Crée une nouvelle réponse DNS
     pub fn new_response(query: &DnsMessage) -> Self {
         let mut header = DnsHeader::new(query.header.id, true);
         header.qdcount = query.header.qdcount;
+        header.set_opcode(query.header.opcode());
+        header.set_recursion_desired(query.header.recursion_desired());
+        header.set_checking_disabled(query.header.checking_disabled());
         
         DnsMessage {
             header,
             questions: query.questions.clone(),
             answers: vec![],
+            authorities: vec![],
+            additionals: vec![],
         }
     }
 
-    /// Sérialise le message DNS complet en bytes
+    /// Indique si ce message porte exactement la section question de `query`,
+    /// ce qu'une réponse doit faire pour être acceptée
+    pub fn same_questions(&self, query: &DnsMessage) -> bool {
+        self.questions.len() == query.questions.len()
+            && self.questions.iter().zip(&query.questions).all(|(question, autre)| question.matches(autre))
+    }
+
+    /// Ajoute un enregistrement à la section réponse et met à jour l'en-tête
+    pub fn add_answer(&mut self, answer: DnsAnswer) {
+        self.answers.push(answer);
+        self.header.ancount += 1;
+    }
+
+    /// Ajoute un enregistrement à la section autorité et met à jour l'en-tête
+    pub fn add_authority(&mut self, record: DnsAnswer) {
+        self.authorities.push(record);
+        self.header.nscount += 1;
+    }
+
+    /// Ajoute un enregistrement à la section additionnelle et met à jour l'en-tête
+    pub fn add_additional(&mut self, record: DnsAnswer) {
+        self.additionals.push(record);
+        self.header.arcount += 1;
+    }
+
+    /// Sérialise le message DNS complet en bytes, en compressant les noms répétés
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
+        let mut table = TableCompression::new();
         
         // En-tête
         bytes.extend_from_slice(&self.header.to_bytes());
         
         // Questions
         for question in &self.questions {
-            bytes.extend_from_slice(&question.to_bytes());
+            question.write_compressed(&mut bytes, &mut table);
         }
         
-        // Réponses
-        for answer in &self.answers {
-            bytes.extend_from_slice(&answer.to_bytes());
+        // Réponses, autorité puis données additionnelles
+        for record in self.answers.iter().chain(&self.authorities).chain(&self.additionals) {
+            record.write_compressed(&mut bytes, &mut table);
         }
         
         bytes
     }
 
-    /// Désérialise un message DNS depuis des bytes
-    pub fn from_bytes(data: &[u8]) -> Result<Self, std::io::Error> {
-        if data.len() < 12 {
-            return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Données insuffisantes pour l'en-tête"));
+    /// Sérialise le message en respectant une taille maximale : si elle est dépassée,
+    /// les enregistrements sont retirés (sauf l'OPT) et le bit TC (Truncated) est positionné
+    pub fn to_bytes_truncated(&self, max: usize) -> Vec<u8> {
+        let bytes = self.to_bytes();
+        if bytes.len() <= max {
+            return bytes;
         }
 
-        let header = DnsHeader::from_bytes(&data[0..12])?;
+        let mut tronque = self.clone();
+        tronque.header.set_truncated(true);
+        tronque.header.ancount = 0;
+        tronque.header.nscount = 0;
+        tronque.answers.clear();
+        tronque.authorities.clear();
+        tronque.additionals.retain(|record| record.rtype == DNS_TYPE_OPT);
+        tronque.header.arcount = tronque.additionals.len() as u16;
+        tronque.to_bytes()
+    }
+
+    /// Désérialise un message DNS depuis des bytes, en mode strict
+    pub fn from_bytes(data: &[u8]) -> Result<Self, DnsError> {
+        Self::from_bytes_with_mode(data, ParseMode::Strict)
+    }
+
+    /// Désérialise un message DNS avec la tolérance indiquée
+    pub fn from_bytes_with_mode(data: &[u8], mode: ParseMode) -> Result<Self, DnsError> {
+        let mut header = DnsHeader::from_bytes(data)?;
         let mut pos = 12;
         let mut questions = Vec::new();
 
-        // Parse les questions
+        // Parse les questions : elles sont indispensables, même en mode tolérant
         for _ in 0..header.qdcount {
-            if pos >= data.len() {
-                return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Données insuffisantes pour les questions"));
-            }
-
             let (domain, new_pos) = DnsQuestion::decode_domain_name(data, pos)?;
             pos = new_pos;
 
-            if pos + 4 > data.len() {
-                return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Données insuffisantes pour le type/classe"));
-            }
-
-            let mut cursor = Cursor::new(&data[pos..pos + 4]);
-            let qtype = cursor.read_u16::<BigEndian>()?;
-            let qclass = cursor.read_u16::<BigEndian>()?;
-            pos += 4;
-
             questions.push(DnsQuestion {
                 qname: domain,
-                qtype,
-                qclass,
+                qtype: lire_u16(data, pos)?,
+                qclass: lire_u16(data, pos + 2)?,
             });
+            pos += 4;
         }
 
-        // Parse les réponses
-        let mut answers = Vec::new();
-        for _ in 0..header.ancount {
-            if pos >= data.len() {
-                break; // Pas assez de données pour les réponses
+        // Parse les réponses, l'autorité et les données additionnelles
+        let answers = Self::parse_records(data, &mut pos, header.ancount, "réponse", mode)?;
+        let authorities = Self::parse_records(data, &mut pos, header.nscount, "autorité", mode)?;
+        let additionals = Self::parse_records(data, &mut pos, header.arcount, "additionnelle", mode)?;
+
+        match mode {
+            ParseMode::Strict if pos < data.len() => {
+                return Err(DnsError::TrailingBytes { count: data.len() - pos });
             }
+            ParseMode::Strict => {}
+            ParseMode::Lenient => {
+                header.ancount = answers.len() as u16;
+                header.nscount = authorities.len() as u16;
+                header.arcount = additionals.len() as u16;
+            }
+        }
 
-            // Décode le nom de domaine de la réponse
-            let (domain, new_pos) = DnsQuestion::decode_domain_name(data, pos)?;
-            pos = new_pos;
+        Ok(DnsMessage {
+            header,
+            questions,
+            answers,
+            authorities,
+            additionals,
+        })
+    }
 
-            if pos + 10 > data.len() {
-                break; // Pas assez de données pour type, classe, TTL, rdlength
+    /// Parse `count` enregistrements à partir de `pos`. En mode tolérant, la lecture
+    /// s'arrête au premier enregistrement incomplet.
+    fn parse_records(data: &[u8], pos: &mut usize, count: u16, section: &'static str, mode: ParseMode) -> Result<Vec<DnsAnswer>, DnsError> {
+        let mut records = Vec::new();
+        for _ in 0..count {
+            if *pos >= data.len() {
+                if mode == ParseMode::Lenient {
+                    break;
+                }
+                return Err(DnsError::CountMismatch { section, expected: count, found: records.len() as u16 });
             }
 
-            let mut cursor = Cursor::new(&data[pos..pos + 10]);
-            let rtype = cursor.read_u16::<BigEndian>()?;
-            let rclass = cursor.read_u16::<BigEndian>()?;
-            let ttl = cursor.read_u32::<BigEndian>()?;
-            let rdlength = cursor.read_u16::<BigEndian>()?;
-            pos += 10;
+            match Self::parse_record(data, *pos) {
+                Ok((record, suite)) => {
+                    records.push(record);
+                    *pos = suite;
+                }
+                Err(DnsError::UnexpectedEnd { .. } | DnsError::BadLabelLength { .. }) if mode == ParseMode::Lenient => {
+                    *pos = data.len();
+                    break;
+                }
+                Err(erreur) => return Err(erreur),
+            }
+        }
+        Ok(records)
+    }
+
+    /// Parse un enregistrement commençant à `pos` et retourne la position qui le suit
+    fn parse_record(data: &[u8], pos: usize) -> Result<(DnsAnswer, usize), DnsError> {
+        // Décode le nom de domaine de l'enregistrement
+        let (domain, pos) = DnsQuestion::decode_domain_name(data, pos)?;
 
-            if pos + rdlength as usize > data.len() {
-                break; // Pas assez de données pour rdata
+        if pos + 10 > data.len() {
+            return Err(DnsError::UnexpectedEnd { offset: pos });
+        }
+
+        let mut cursor = Cursor::new(&data[pos..pos + 10]);
+        let rtype = cursor.read_u16::<BigEndian>().unwrap();
+        let rclass = cursor.read_u16::<BigEndian>().unwrap();
+        let ttl = cursor.read_u32::<BigEndian>().unwrap();
+        let rdlength = cursor.read_u16::<BigEndian>().unwrap() as usize;
+        let pos = pos + 10;
+
+        if pos + rdlength > data.len() {
+            return Err(DnsError::UnexpectedEnd { offset: pos });
+        }
+
+        // RDATA vide : admise pour les mises à jour dynamiques (classes ANY et NONE, RFC 2136)
+        // et pour le pseudo-enregistrement OPT, invalide pour tout autre enregistrement
+        let rdata = if rdlength == 0 {
+            if !matches!(rclass, DNS_CLASS_ANY | DNS_CLASS_NONE) && rtype != DNS_TYPE_OPT {
+                return Err(DnsError::BadRdata { rtype, offset: pos });
             }
+            RData::Unknown(Vec::new())
+        } else {
+            RData::decode(rtype, data, pos, rdlength)?
+        };
+        Ok((DnsAnswer {
+            name: domain,
+            rtype,
+            rclass,
+            ttl,
+            rdata,
+        }, pos + rdlength))
+    }
+}
 
-            let rdata = data[pos..pos + rdlength as usize].to_vec();
-            pos += rdlength as usize;
+/// Lit un entier 16 bits big-endian à la position donnée
+fn lire_u16(data: &[u8], pos: usize) -> Result<u16, DnsError> {
+    match data.get(pos..pos + 2) {
+        Some(octets) => Ok(u16::from_be_bytes([octets[0], octets[1]])),
+        None => Err(DnsError::UnexpectedEnd { offset: pos }),
+    }
+}
 
-            answers.push(DnsAnswer {
-                name: domain,
-                rtype,
-                rclass,
-                ttl,
-                rdlength,
-                rdata,
-            });
+/// Forme absolue d'un nom pour l'affichage (avec le point final)
+pub(crate) fn fqdn(nom: &str) -> String {
+    format!("{}.", nom.trim_end_matches('.'))
+}
+
+/// Labels d'un nom, découpé sur les '.' non échappés (les labels vides sont ignorés)
+fn labels(domain: &str) -> Vec<&str> {
+    let mut labels = Vec::new();
+    let mut debut = 0;
+    let mut octets = domain.bytes().enumerate();
+    while let Some((i, octet)) = octets.next() {
+        match octet {
+            b'\\' => {
+                octets.next();
+            }
+            b'.' => {
+                labels.push(&domain[debut..i]);
+                debut = i + 1;
+            }
+            _ => {}
         }
+    }
+    labels.push(&domain[debut..]);
+    labels.retain(|label| !label.is_empty());
+    labels
+}
 
-        Ok(DnsMessage {
-            header,
-            questions,
-            answers,
-        })
+/// Octets d'un label : `\X` donne le caractère X, `\DDD` l'octet de valeur décimale DDD
+fn octets_label(label: &str) -> Vec<u8> {
+    let brut = label.as_bytes();
+    let mut octets = Vec::with_capacity(brut.len());
+    let mut i = 0;
+    while i < brut.len() {
+        let decimal = brut.get(i + 1..i + 4).filter(|chiffres| chiffres.iter().all(u8::is_ascii_digit));
+        match (brut[i], decimal) {
+            (b'\\', Some(chiffres)) => {
+                let valeur = chiffres.iter().fold(0u16, |valeur, chiffre| valeur * 10 + (chiffre - b'0') as u16);
+                octets.push(valeur as u8);
+                i += 4;
+            }
+            (b'\\', None) if i + 1 < brut.len() => {
+                octets.push(brut[i + 1]);
+                i += 2;
+            }
+            (octet, _) => {
+                octets.push(octet);
+                i += 1;
+            }
+        }
+    }
+    octets
+}
+
+/// Nom de résolution inverse d'une adresse IP : `in-addr.arpa` pour IPv4,
+/// `ip6.arpa` (quartets dans l'ordre inverse) pour IPv6
+pub fn reverse_name(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => {
+            let o = ip.octets();
+            format!("{}.{}.{}.{}.in-addr.arpa", o[3], o[2], o[1], o[0])
+        }
+        IpAddr::V6(ip) => {
+            let mut nom = String::new();
+            for octet in ip.octets().iter().rev() {
+                nom.push_str(&format!("{:x}.{:x}.", octet & 0x0F, octet >> 4));
+            }
+            nom.push_str("ip6.arpa");
+            nom
+        }
     }
 }
 
 /// Types de requêtes DNS constants
 pub const DNS_TYPE_A: u16 = 1;     // Adresse IPv4
 pub const DNS_TYPE_AAAA: u16 = 28; // Adresse IPv6
-pub const DNS_TYPE_CNAME: u16 = 5; // Nom canonique
\ No newline at end of file
+pub const DNS_TYPE_CNAME: u16 = 5; // Nom canonique
+pub const DNS_TYPE_NS: u16 = 2;    // Serveur de noms
+pub const DNS_TYPE_SOA: u16 = 6;   // Début d'autorité
+pub const DNS_TYPE_PTR: u16 = 12;  // Pointeur (résolution inverse)
+pub const DNS_TYPE_MX: u16 = 15;   // Serveur de messagerie
+pub const DNS_TYPE_TXT: u16 = 16;  // Texte
+pub const DNS_TYPE_SRV: u16 = 33;  // Localisation de service
+pub const DNS_TYPE_OPT: u16 = 41;  // Pseudo-enregistrement EDNS(0)
+pub const DNS_TYPE_TSIG: u16 = 250; // Signature de transaction
+pub const DNS_TYPE_IXFR: u16 = 251; // Transfert de zone incrémental
+pub const DNS_TYPE_AXFR: u16 = 252; // Transfert de zone complet
+pub const DNS_TYPE_ANY: u16 = 255;  // Tous les types (mises à jour dynamiques)
+
+/// Classes DNS constantes
+pub const DNS_CLASS_IN: u16 = 1;     // Internet
+pub const DNS_CLASS_NONE: u16 = 254; // Suppression d'un enregistrement précis (RFC 2136)
+pub const DNS_CLASS_ANY: u16 = 255;  // Toutes les classes
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reponse_exemple() -> DnsMessage {
+        let requete = DnsMessage::new_query(42, "www.exemple.com".to_string(), DNS_TYPE_A).unwrap();
+        let mut reponse = DnsMessage::new_response(&requete);
+        reponse.add_answer(DnsAnswer::new_a_record("www.exemple.com".to_string(), [192, 168, 1, 101], 300));
+        reponse.add_answer(DnsAnswer::new_a_record("exemple.com".to_string(), [192, 168, 1, 100], 300));
+        reponse
+    }
+
+    #[test]
+    fn test_compression_aller_retour() {
+        let reponse = reponse_exemple();
+        let bytes = reponse.to_bytes();
+        let decode = DnsMessage::from_bytes(&bytes).unwrap();
+
+        assert_eq!(decode.questions[0].qname, "www.exemple.com");
+        assert_eq!(decode.answers[0].name, "www.exemple.com");
+        assert_eq!(decode.answers[1].name, "exemple.com");
+        assert_eq!(decode.answers[1].rdata, RData::A([192, 168, 1, 100].into()));
+    }
+
+    #[test]
+    fn test_compression_reduit_la_taille() {
+        let reponse = reponse_exemple();
+        let non_compresse: usize = 12
+            + reponse.questions.iter().map(|q| q.to_bytes().len()).sum::<usize>()
+            + reponse.answers.iter().map(|a| a.to_bytes().len()).sum::<usize>();
+
+        // Chaque réponse remplace son nom par un pointeur de 2 octets
+        assert_eq!(reponse.to_bytes().len(), non_compresse - 15 - 11);
+    }
+
+    #[test]
+    fn test_sections_autorite_et_additionnelle() {
+        let mut reponse = reponse_exemple();
+        reponse.add_authority(DnsAnswer::new_a_record("ns1.exemple.com".to_string(), [192, 168, 1, 1], 3600));
+        reponse.add_additional(DnsAnswer::new_a_record("ns2.exemple.com".to_string(), [192, 168, 1, 2], 3600));
+
+        let decode = DnsMessage::from_bytes(&reponse.to_bytes()).unwrap();
+        assert_eq!(decode.header.nscount, 1);
+        assert_eq!(decode.header.arcount, 1);
+        assert_eq!(decode.answers.len(), 2);
+        assert_eq!(decode.authorities[0].name, "ns1.exemple.com");
+        assert_eq!(decode.additionals[0].name, "ns2.exemple.com");
+        assert_eq!(decode.additionals[0].rdata, RData::A([192, 168, 1, 2].into()));
+    }
+
+    #[test]
+    fn test_rdata_types_aller_retour() {
+        let donnees = vec![
+            RData::Aaaa("2001:db8::1".parse().unwrap()),
+            RData::Cname("www.exemple.com".to_string()),
+            RData::Mx { preference: 10, exchange: "mail.exemple.com".to_string() },
+            RData::Txt(vec![b"v=spf1 -all".to_vec(), b"bonjour".to_vec()]),
+            RData::Ns("ns1.exemple.com".to_string()),
+            RData::Soa {
+                mname: "ns1.exemple.com".to_string(),
+                rname: "admin.exemple.com".to_string(),
+                serial: 2024010101,
+                refresh: 3600,
+                retry: 600,
+                expire: 86400,
+                minimum: 300,
+            },
+            RData::Ptr("exemple.com".to_string()),
+            RData::Srv { priority: 10, weight: 5, port: 5060, target: "sip.exemple.com".to_string() },
+        ];
+
+        let mut reponse = reponse_exemple();
+        for rdata in &donnees {
+            reponse.add_answer(DnsAnswer::new("exemple.com".to_string(), 60, rdata.clone()));
+        }
+
+        let decode = DnsMessage::from_bytes(&reponse.to_bytes()).unwrap();
+        let decodees: Vec<RData> = decode.answers[2..].iter().map(|a| a.rdata.clone()).collect();
+        assert_eq!(decodees, donnees);
+        assert_eq!(decode.answers[4].rtype, DNS_TYPE_MX);
+    }
+
+    #[test]
+    fn test_rdata_affichage_et_types() {
+        let mx = RData::Mx { preference: 10, exchange: "mail.exemple.com".to_string() };
+        assert_eq!(mx.to_string(), "10 mail.exemple.com.");
+        assert_eq!(RData::Unknown(vec![0xAB, 0x01]).to_string(), "\\# 2 ab01");
+
+        // Chaînes TXT : octets conservés tels quels, échappés seulement à l'affichage
+        let txt = RData::Txt(vec![vec![0xff, 0xfe], b"a\"b\\".to_vec()]);
+        let mut message = DnsMessage::new_query(1, "exemple.com".to_string(), DNS_TYPE_TXT).unwrap();
+        message.add_answer(DnsAnswer::new("exemple.com".to_string(), 60, txt.clone()));
+        assert_eq!(DnsMessage::from_bytes(&message.to_bytes()).unwrap().answers[0].rdata, txt);
+        assert_eq!(txt.to_string(), "\"\\255\\254\" \"a\\\"b\\\\\"");
+        assert_eq!(type_from_str("aaaa"), Some(DNS_TYPE_AAAA));
+        assert_eq!(type_from_str("TYPE99"), Some(99));
+        assert_eq!(type_to_string(DNS_TYPE_SRV), "SRV");
+    }
+
+    #[test]
+    fn test_troncature_udp() {
+        let mut reponse = reponse_exemple();
+        reponse.add_answer(DnsAnswer::new("exemple.com".to_string(), 60, RData::Txt(vec![vec![b'x'; 600]])));
+
+        let complet = reponse.to_bytes();
+        assert!(complet.len() > MAX_UDP_SIZE);
+
+        let tronque = DnsMessage::from_bytes(&reponse.to_bytes_truncated(MAX_UDP_SIZE)).unwrap();
+        assert!(tronque.header.truncated());
+        assert!(tronque.answers.is_empty());
+        assert_eq!(tronque.questions[0].qname, "www.exemple.com");
+
+        // Un message assez petit est envoyé tel quel
+        assert_eq!(reponse_exemple().to_bytes_truncated(MAX_UDP_SIZE), reponse_exemple().to_bytes());
+    }
+
+    #[test]
+    fn test_drapeaux_en_tete() {
+        let mut header = DnsHeader::new(1, false);
+        assert!(!header.is_response());
+        assert!(header.recursion_desired());
+        assert_eq!(header.opcode(), Opcode::Query);
+
+        header.set_opcode(Opcode::Update);
+        header.set_authoritative(true);
+        header.set_checking_disabled(true);
+        header.set_rcode(Rcode::NxDomain);
+        header.set_rcode(Rcode::NotImp);
+        assert_eq!(header.flags, 0x2D14);
+        assert_eq!(header.opcode(), Opcode::Update);
+        assert_eq!(header.rcode(), Rcode::NotImp);
+
+        header.set_recursion_desired(false);
+        assert!(!header.recursion_desired());
+        assert!(header.authoritative());
+        assert_eq!(Rcode::from(23), Rcode::Unknown(23));
+        assert_eq!(Rcode::NxDomain.to_string(), "NXDOMAIN");
+    }
+
+    #[test]
+    fn test_decodage_pointeur() {
+        // "exemple.com" à la position 0, puis "www" suivi d'un pointeur vers 0
+        let mut data = DnsQuestion::encode_domain_name("exemple.com").unwrap();
+        let debut = data.len();
+        data.extend_from_slice(&[3, b'w', b'w', b'w', 0xC0, 0x00, 0xAA]);
+
+        let (nom, pos) = DnsQuestion::decode_domain_name(&data, debut).unwrap();
+        assert_eq!(nom, "www.exemple.com");
+        assert_eq!(pos, data.len() - 1);
+
+        // Un '.' ou un octet non imprimable dans un label est échappé et se réencode à l'identique
+        for (brut, attendu) in [(&b"\x03a.b\x00"[..], "a\\.b"), (b"\x02\xff\\\x01c\x00", "\\255\\\\.c")] {
+            let (nom, _) = DnsQuestion::decode_domain_name(brut, 0).unwrap();
+            assert_eq!(nom, attendu);
+            let mut encode = Vec::new();
+            DnsQuestion::write_domain_name(&nom, &mut encode);
+            assert_eq!(encode, brut);
+            let mut compresse = Vec::new();
+            DnsQuestion::encode_domain_name_compressed(&nom, &mut compresse, &mut TableCompression::new());
+            assert_eq!(compresse, brut);
+        }
+    }
+
+    #[test]
+    fn test_erreurs_de_decodage() {
+        let donnees = reponse_exemple().to_bytes();
+        assert_eq!(DnsMessage::from_bytes(&donnees[..8]).unwrap_err(), DnsError::TruncatedHeader);
+
+        // Octets en trop : refusés en mode strict, ignorés en mode tolérant
+        let mut avec_surplus = donnees.clone();
+        avec_surplus.extend_from_slice(&[0, 0, 0]);
+        assert_eq!(DnsMessage::from_bytes(&avec_surplus).unwrap_err(), DnsError::TrailingBytes { count: 3 });
+        assert!(DnsMessage::from_bytes_with_mode(&avec_surplus, ParseMode::Lenient).is_ok());
+
+        // Un enregistrement annoncé en plus, absent du message
+        let mut incomplet = donnees.clone();
+        incomplet[7] += 1;
+        let attendu = reponse_exemple().answers.len() as u16 + 1;
+        assert_eq!(
+            DnsMessage::from_bytes(&incomplet).unwrap_err(),
+            DnsError::CountMismatch { section: "réponse", expected: attendu, found: attendu - 1 }
+        );
+        let tolere = DnsMessage::from_bytes_with_mode(&incomplet, ParseMode::Lenient).unwrap();
+        assert_eq!(tolere.header.ancount, attendu - 1);
+
+        // Dernier enregistrement coupé en plein milieu
+        let coupe = &donnees[..donnees.len() - 2];
+        assert!(matches!(DnsMessage::from_bytes(coupe), Err(DnsError::UnexpectedEnd { .. })));
+        let tolere = DnsMessage::from_bytes_with_mode(coupe, ParseMode::Lenient).unwrap();
+        assert_eq!(tolere.answers.len(), 1);
+        assert_eq!(tolere.header.ancount, 1);
+
+        // Enregistrement A sans RDATA : refusé, sauf en classe ANY (mise à jour dynamique)
+        let mut vide = DnsMessage::new_query(1, "a.com".to_string(), DNS_TYPE_A).unwrap().to_bytes();
+        vide[7] = 1;
+        let debut_rr = vide.len();
+        vide.extend_from_slice(&[0, 0, 1, 0, 1, 0, 0, 0, 60, 0, 0]);
+        assert_eq!(DnsMessage::from_bytes(&vide).unwrap_err(), DnsError::BadRdata { rtype: DNS_TYPE_A, offset: debut_rr + 11 });
+        vide[debut_rr + 3..debut_rr + 5].copy_from_slice(&DNS_CLASS_ANY.to_be_bytes());
+        assert_eq!(DnsMessage::from_bytes(&vide).unwrap().answers[0].rdata, RData::Unknown(Vec::new()));
+
+        // Label annonçant plus d'octets qu'il n'en reste, nom de plus de 255 octets
+        assert_eq!(DnsQuestion::decode_domain_name(&[5, b'a', b'b'], 0).unwrap_err(), DnsError::BadLabelLength { offset: 0, length: 5 });
+        let mut long = Vec::new();
+        for _ in 0..5 {
+            long.push(63);
+            long.extend_from_slice(&[b'a'; 63]);
+        }
+        long.push(0);
+        assert!(matches!(DnsQuestion::decode_domain_name(&long, 0), Err(DnsError::NameTooLong { .. })));
+    }
+
+    #[test]
+    fn test_validation_des_noms() {
+        assert_eq!(DnsQuestion::validate_domain_name("Www.Exemple.com.").unwrap(), "Www.Exemple.com");
+        assert_eq!(DnsQuestion::validate_domain_name("_sip._udp.exemple.com").unwrap(), "_sip._udp.exemple.com");
+        assert_eq!(DnsQuestion::validate_domain_name("*.exemple.com").unwrap(), "*.exemple.com");
+        assert_eq!(DnsQuestion::validate_domain_name("bücher.de").unwrap(), "xn--bcher-kva.de");
+
+        let long = "a".repeat(64);
+        assert_eq!(DnsQuestion::validate_domain_name(&long).unwrap_err(), DnsError::LabelTooLong { label: long });
+        let trop_long = vec!["a".repeat(63); 4].join(".");
+        assert_eq!(DnsQuestion::validate_domain_name(&trop_long).unwrap_err(), DnsError::NameTooLong { length: 257 });
+        for invalide in ["a..b", "a b.com", "www.*.com", "exemple.com/"] {
+            assert!(matches!(DnsQuestion::validate_domain_name(invalide), Err(DnsError::InvalidName { .. })), "{}", invalide);
+        }
+        assert!(DnsMessage::new_query(1, "a".repeat(70), DNS_TYPE_A).is_err());
+    }
+
+    #[test]
+    fn test_nom_inverse() {
+        assert_eq!(reverse_name("192.168.1.100".parse().unwrap()), "100.1.168.192.in-addr.arpa");
+        assert_eq!(
+            reverse_name("2001:db8::1".parse().unwrap()),
+            "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa"
+        );
+    }
+
+    #[test]
+    fn test_boucle_de_pointeurs() {
+        // Deux pointeurs qui se référencent mutuellement
+        let data = [0xC0, 0x02, 0xC0, 0x00];
+        let erreur = DnsQuestion::decode_domain_name(&data, 0).unwrap_err();
+        assert_eq!(erreur, DnsError::PointerLoop { offset: 0 });
+
+        // Nom dont le pointeur renvoie à son propre début
+        let data = [1, b'a', 0xC0, 0x00];
+        assert!(DnsQuestion::decode_domain_name(&data, 0).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::{type_to_string, CacheStats, Rcode};
+
+/// Bornes supérieures (en secondes) des intervalles de l'histogramme de latence
+pub const LATENCY_BUCKETS: [f64; 12] = [0.0001, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0];
+
+/// Compteurs du serveur exposés au format texte de Prometheus
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    queries: BTreeMap<String, u64>,
+    responses: BTreeMap<String, u64>,
+    dropped: BTreeMap<String, u64>,
+    /// Nombre d'observations de chaque intervalle (non cumulé), la dernière case pour +Inf
+    latency_buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    latency_sum: f64,
+    latency_count: u64,
+}
+
+impl Metrics {
+    /// Crée des compteurs à zéro
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compte une requête reçue pour le type `qtype`
+    pub fn record_query(&mut self, qtype: u16) {
+        *self.queries.entry(type_to_string(qtype)).or_default() += 1;
+    }
+
+    /// Compte une réponse envoyée et son temps de traitement
+    pub fn record_response(&mut self, rcode: Rcode, latency: Duration) {
+        *self.responses.entry(rcode.to_string()).or_default() += 1;
+        let secondes = latency.as_secs_f64();
+        let index = LATENCY_BUCKETS.iter().position(|&borne| secondes <= borne).unwrap_or(LATENCY_BUCKETS.len());
+        self.latency_buckets[index] += 1;
+        self.latency_sum += secondes;
+        self.latency_count += 1;
+    }
+
+    /// Compte un paquet reçu resté sans réponse, pour la raison donnée
+    pub fn record_drop(&mut self, reason: &str) {
+        *self.dropped.entry(reason.to_string()).or_default() += 1;
+    }
+
+    /// Nombre de paquets ignorés pour la raison donnée
+    pub fn dropped(&self, reason: &str) -> u64 {
+        self.dropped.get(reason).copied().unwrap_or(0)
+    }
+
+    /// Texte d'exposition Prometheus (version 0.0.4), avec les compteurs du cache s'il est actif
+    pub fn render(&self, cache: Option<&CacheStats>) -> String {
+        let mut texte = String::new();
+        let mut famille = |nom: &str, aide: &str, genre: &str, valeurs: Vec<(String, String)>| {
+            let _ = writeln!(texte, "# HELP {} {}", nom, aide);
+            let _ = writeln!(texte, "# TYPE {} {}", nom, genre);
+            for (etiquettes, valeur) in valeurs {
+                let _ = writeln!(texte, "{}{} {}", nom, etiquettes, valeur);
+            }
+        };
+        let par_etiquette = |compteurs: &BTreeMap<String, u64>, etiquette: &str| -> Vec<(String, String)> {
+            compteurs
+                .iter()
+                .map(|(valeur, total)| (format!("{{{}=\"{}\"}}", etiquette, echapper(valeur)), total.to_string()))
+                .collect()
+        };
+
+        famille("dns_queries_total", "Requêtes reçues, par type demandé.", "counter", par_etiquette(&self.queries, "qtype"));
+        famille("dns_responses_total", "Réponses envoyées, par code de réponse.", "counter", par_etiquette(&self.responses, "rcode"));
+        famille("dns_dropped_packets_total", "Paquets reçus restés sans réponse, par raison.", "counter", par_etiquette(&self.dropped, "reason"));
+
+        let mut cumul = 0;
+        let mut histogramme: Vec<(String, String)> = LATENCY_BUCKETS
+            .iter()
+            .zip(&self.latency_buckets)
+            .map(|(borne, nombre)| {
+                cumul += nombre;
+                (format!("_bucket{{le=\"{}\"}}", borne), cumul.to_string())
+            })
+            .collect();
+        histogramme.push(("_bucket{le=\"+Inf\"}".to_string(), self.latency_count.to_string()));
+        histogramme.push(("_sum".to_string(), self.latency_sum.to_string()));
+        histogramme.push(("_count".to_string(), self.latency_count.to_string()));
+        famille("dns_response_duration_seconds", "Temps de traitement des requêtes.", "histogram", histogramme);
+
+        if let Some(cache) = cache {
+            famille("dns_cache_hits_total", "Réponses servies depuis le cache.", "counter", vec![(String::new(), cache.hits.to_string())]);
+            famille("dns_cache_misses_total", "Recherches absentes du cache.", "counter", vec![(String::new(), cache.misses.to_string())]);
+            famille("dns_cache_entries", "Réponses actuellement en cache.", "gauge", vec![(String::new(), cache.entries.to_string())]);
+        }
+        texte
+    }
+}
+
+/// Échappe une valeur d'étiquette (barre oblique inverse, guillemet, saut de ligne)
+fn echapper(valeur: &str) -> String {
+    valeur.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DNS_TYPE_A, DNS_TYPE_MX};
+
+    #[test]
+    fn test_exposition_prometheus() {
+        let mut metriques = Metrics::new();
+        metriques.record_query(DNS_TYPE_A);
+        metriques.record_query(DNS_TYPE_A);
+        metriques.record_query(DNS_TYPE_MX);
+        metriques.record_response(Rcode::NoError, Duration::from_micros(300));
+        metriques.record_response(Rcode::NxDomain, Duration::from_secs(2));
+        metriques.record_drop("rrl");
+
+        let cache = CacheStats { hits: 4, misses: 1, entries: 1 };
+        let texte = metriques.render(Some(&cache));
+        for ligne in [
+            "# TYPE dns_queries_total counter",
+            "dns_queries_total{qtype=\"A\"} 2",
+            "dns_queries_total{qtype=\"MX\"} 1",
+            "dns_responses_total{rcode=\"NXDOMAIN\"} 1",
+            "dns_dropped_packets_total{reason=\"rrl\"} 1",
+            "dns_response_duration_seconds_bucket{le=\"0.0001\"} 0",
+            "dns_response_duration_seconds_bucket{le=\"0.0005\"} 1",
+            "dns_response_duration_seconds_bucket{le=\"1\"} 1",
+            "dns_response_duration_seconds_bucket{le=\"+Inf\"} 2",
+            "dns_response_duration_seconds_count 2",
+            "dns_cache_hits_total 4",
+        ] {
+            assert!(texte.lines().any(|l| l == ligne), "ligne absente: {}\n{}", ligne, texte);
+        }
+        assert!(!Metrics::new().render(None).contains("dns_cache"));
+    }
+}
@@ -0,0 +1,148 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{type_to_string, DnsMessage};
+
+/// Événement du journal des requêtes : une requête et la réponse construite par le serveur
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryLogEntry {
+    pub timestamp: DateTime<Utc>,
+    pub client: IpAddr,
+    /// Transport de la requête : `udp` ou `tcp`
+    pub protocol: String,
+    pub id: u16,
+    pub qname: String,
+    pub qtype: String,
+    pub rcode: String,
+    /// Enregistrements de la section réponse (sur tous les messages d'un transfert de zone)
+    pub answers: usize,
+    /// Temps de traitement en microsecondes
+    pub latency_us: u64,
+}
+
+impl QueryLogEntry {
+    /// Décrit l'échange entre `client` et le serveur ; le RCODE est celui du premier message
+    pub fn new(client: IpAddr, protocol: &str, query: &DnsMessage, responses: &[DnsMessage], latency: Duration) -> Self {
+        let question = query.questions.first();
+        QueryLogEntry {
+            timestamp: Utc::now(),
+            client,
+            protocol: protocol.to_string(),
+            id: query.header.id,
+            qname: question.map(|q| q.qname.clone()).unwrap_or_default(),
+            qtype: question.map(|q| type_to_string(q.qtype)).unwrap_or_default(),
+            rcode: responses.first().map(|reponse| reponse.rcode().to_string()).unwrap_or_default(),
+            answers: responses.iter().map(|reponse| reponse.answers.len()).sum(),
+            latency_us: latency.as_micros().min(u64::MAX as u128) as u64,
+        }
+    }
+}
+
+/// Journal des requêtes au format JSON Lines (un objet par ligne). Le fichier est renommé
+/// en `.1` lorsqu'il atteint sa taille maximale, les archives précédentes en `.2`, `.3`...
+/// et les plus anciennes au-delà de `keep` sont supprimées.
+#[derive(Debug)]
+pub struct QueryLog {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    keep: usize,
+}
+
+impl QueryLog {
+    /// Ouvre (ou crée) le journal `path` en ajout
+    pub fn open(path: &Path, max_size: u64, keep: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(QueryLog { path: path.to_path_buf(), file, size, max_size, keep })
+    }
+
+    /// Chemin du fichier courant
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Ajoute un événement, après rotation si la ligne ferait dépasser la taille maximale
+    pub fn write(&mut self, entry: &QueryLogEntry) -> io::Result<()> {
+        let mut ligne = serde_json::to_vec(entry)?;
+        ligne.push(b'\n');
+        if self.size > 0 && self.size + ligne.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        self.file.write_all(&ligne)?;
+        self.size += ligne.len() as u64;
+        Ok(())
+    }
+
+    /// Chemin de l'archive numéro `index`
+    fn archive(&self, index: usize) -> PathBuf {
+        let mut nom = self.path.clone().into_os_string();
+        nom.push(format!(".{}", index));
+        PathBuf::from(nom)
+    }
+
+    /// Décale les archives, archive le fichier courant et en ouvre un nouveau
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.archive(self.keep));
+            for index in (1..self.keep).rev() {
+                let source = self.archive(index);
+                if source.exists() {
+                    fs::rename(&source, self.archive(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.archive(1))?;
+        }
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DnsAnswer, RData, DNS_TYPE_A};
+
+    #[test]
+    fn test_journal_json_avec_rotation() {
+        let dossier = std::env::temp_dir().join(format!("tp7_journal_{}", std::process::id()));
+        fs::create_dir_all(&dossier).unwrap();
+        let chemin = dossier.join("requetes.log");
+
+        let requete = DnsMessage::new_query(7, "www.exemple.com".to_string(), DNS_TYPE_A).unwrap();
+        let mut reponse = DnsMessage::new_response(&requete);
+        reponse.add_answer(DnsAnswer::new("www.exemple.com".to_string(), 60, RData::A("10.0.0.1".parse().unwrap())));
+        let entree = QueryLogEntry::new("127.0.0.1".parse().unwrap(), "udp", &requete, &[reponse], Duration::from_micros(250));
+        assert_eq!((entree.qtype.as_str(), entree.rcode.as_str(), entree.answers), ("A", "NOERROR", 1));
+
+        // Taille maximale de deux lignes et demie : une rotation toutes les deux lignes
+        let taille_ligne = serde_json::to_vec(&entree).unwrap().len() as u64 + 1;
+        let mut journal = QueryLog::open(&chemin, taille_ligne * 5 / 2, 2).unwrap();
+        for _ in 0..7 {
+            journal.write(&entree).unwrap();
+        }
+
+        let lignes = |chemin: &Path| fs::read_to_string(chemin).unwrap().lines().count();
+        assert_eq!(lignes(&chemin), 1);
+        assert_eq!(lignes(&journal.archive(1)), 2);
+        assert_eq!(lignes(&journal.archive(2)), 2);
+        assert!(!journal.archive(3).exists());
+
+        let relue: QueryLogEntry = serde_json::from_str(fs::read_to_string(&chemin).unwrap().trim()).unwrap();
+        assert_eq!(relue, entree);
+
+        let vide = QueryLogEntry::new("::1".parse().unwrap(), "tcp", &requete, &[], Duration::ZERO);
+        assert_eq!((vide.rcode.as_str(), vide.answers), ("", 0));
+        fs::remove_dir_all(&dossier).unwrap();
+    }
+}
@@ -0,0 +1,298 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::fmt;
+use std::io::Cursor;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::{fqdn, DnsError, DnsQuestion, TableCompression};
+use crate::{DNS_TYPE_A, DNS_TYPE_AAAA, DNS_TYPE_ANY, DNS_TYPE_AXFR, DNS_TYPE_CNAME, DNS_TYPE_IXFR, DNS_TYPE_MX, DNS_TYPE_NS, DNS_TYPE_OPT, DNS_TYPE_PTR, DNS_TYPE_SOA, DNS_TYPE_SRV, DNS_TYPE_TSIG, DNS_TYPE_TXT};
+
+/// Données typées d'un enregistrement DNS (RDATA)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RData {
+    /// Adresse IPv4
+    A(Ipv4Addr),
+    /// Adresse IPv6
+    Aaaa(Ipv6Addr),
+    /// Nom canonique (alias)
+    Cname(String),
+    /// Serveur de noms faisant autorité
+    Ns(String),
+    /// Pointeur (résolution inverse)
+    Ptr(String),
+    /// Serveur de messagerie
+    Mx { preference: u16, exchange: String },
+    /// Chaînes de caractères d'un TXT, octets bruts (pas forcément de l'UTF-8)
+    Txt(Vec<Vec<u8>>),
+    /// Début d'autorité d'une zone
+    Soa {
+        mname: String,
+        rname: String,
+        serial: u32,
+        refresh: u32,
+        retry: u32,
+        expire: u32,
+        minimum: u32,
+    },
+    /// Localisation d'un service
+    Srv { priority: u16, weight: u16, port: u16, target: String },
+    /// Type non géré : données brutes conservées telles quelles
+    Unknown(Vec<u8>),
+}
+
+impl RData {
+    /// Type DNS correspondant aux données (None pour les données brutes)
+    pub fn record_type(&self) -> Option<u16> {
+        match self {
+            RData::A(_) => Some(DNS_TYPE_A),
+            RData::Aaaa(_) => Some(DNS_TYPE_AAAA),
+            RData::Cname(_) => Some(DNS_TYPE_CNAME),
+            RData::Ns(_) => Some(DNS_TYPE_NS),
+            RData::Ptr(_) => Some(DNS_TYPE_PTR),
+            RData::Mx { .. } => Some(DNS_TYPE_MX),
+            RData::Txt(_) => Some(DNS_TYPE_TXT),
+            RData::Soa { .. } => Some(DNS_TYPE_SOA),
+            RData::Srv { .. } => Some(DNS_TYPE_SRV),
+            RData::Unknown(_) => None,
+        }
+    }
+
+    /// Décode les données d'un enregistrement de type `rtype` situées dans `data[pos..pos + longueur]`.
+    /// `data` est le message complet afin de pouvoir suivre les pointeurs de compression.
+    pub fn decode(rtype: u16, data: &[u8], pos: usize, longueur: usize) -> Result<Self, DnsError> {
+        let fin = pos + longueur;
+        if fin > data.len() {
+            return Err(DnsError::UnexpectedEnd { offset: pos });
+        }
+        let invalide = DnsError::BadRdata { rtype, offset: pos };
+        let brut = &data[pos..fin];
+        let mut cursor = Cursor::new(brut);
+
+        let (rdata, consomme) = match rtype {
+            DNS_TYPE_A if longueur == 4 => (RData::A(Ipv4Addr::new(brut[0], brut[1], brut[2], brut[3])), 4),
+            DNS_TYPE_AAAA if longueur == 16 => {
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(brut);
+                (RData::Aaaa(Ipv6Addr::from(octets)), 16)
+            }
+            DNS_TYPE_A | DNS_TYPE_AAAA => {
+                return Err(invalide);
+            }
+            DNS_TYPE_CNAME | DNS_TYPE_NS | DNS_TYPE_PTR => {
+                let (nom, suite) = DnsQuestion::decode_domain_name(data, pos)?;
+                let rdata = match rtype {
+                    DNS_TYPE_CNAME => RData::Cname(nom),
+                    DNS_TYPE_NS => RData::Ns(nom),
+                    _ => RData::Ptr(nom),
+                };
+                (rdata, suite - pos)
+            }
+            DNS_TYPE_MX => {
+                let preference = cursor.read_u16::<BigEndian>().map_err(|_| invalide.clone())?;
+                let (exchange, suite) = DnsQuestion::decode_domain_name(data, pos + 2)?;
+                (RData::Mx { preference, exchange }, suite - pos)
+            }
+            DNS_TYPE_TXT => {
+                let mut textes = Vec::new();
+                let mut i = 0;
+                while i < longueur {
+                    let taille = brut[i] as usize;
+                    if i + 1 + taille > longueur {
+                        return Err(invalide);
+                    }
+                    textes.push(brut[i + 1..i + 1 + taille].to_vec());
+                    i += 1 + taille;
+                }
+                (RData::Txt(textes), longueur)
+            }
+            DNS_TYPE_SOA => {
+                let (mname, suite) = DnsQuestion::decode_domain_name(data, pos)?;
+                let (rname, suite) = DnsQuestion::decode_domain_name(data, suite)?;
+                if suite + 20 > fin {
+                    return Err(invalide);
+                }
+                let mut cursor = Cursor::new(&data[suite..suite + 20]);
+                let soa = RData::Soa {
+                    mname,
+                    rname,
+                    serial: cursor.read_u32::<BigEndian>().unwrap(),
+                    refresh: cursor.read_u32::<BigEndian>().unwrap(),
+                    retry: cursor.read_u32::<BigEndian>().unwrap(),
+                    expire: cursor.read_u32::<BigEndian>().unwrap(),
+                    minimum: cursor.read_u32::<BigEndian>().unwrap(),
+                };
+                (soa, suite + 20 - pos)
+            }
+            DNS_TYPE_SRV => {
+                if longueur < 6 {
+                    return Err(invalide);
+                }
+                let priority = cursor.read_u16::<BigEndian>().unwrap();
+                let weight = cursor.read_u16::<BigEndian>().unwrap();
+                let port = cursor.read_u16::<BigEndian>().unwrap();
+                let (target, suite) = DnsQuestion::decode_domain_name(data, pos + 6)?;
+                (RData::Srv { priority, weight, port, target }, suite - pos)
+            }
+            _ => (RData::Unknown(brut.to_vec()), longueur),
+        };
+
+        if consomme != longueur {
+            return Err(invalide);
+        }
+        Ok(rdata)
+    }
+
+    /// Encode les données sans compression des noms
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write(&mut bytes, None);
+        bytes
+    }
+
+    /// Écrit les données dans un message en cours de construction, avec compression des noms
+    /// lorsque la RFC 3597 l'autorise (CNAME, NS, PTR, MX, SOA)
+    pub(crate) fn write_compressed(&self, buffer: &mut Vec<u8>, table: &mut TableCompression) {
+        self.write(buffer, Some(table));
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>, mut table: Option<&mut TableCompression>) {
+        let mut ecrire_nom = |buffer: &mut Vec<u8>, nom: &str| match table.as_deref_mut() {
+            Some(table) => DnsQuestion::encode_domain_name_compressed(nom, buffer, table),
+            None => DnsQuestion::write_domain_name(nom, buffer),
+        };
+
+        match self {
+            RData::A(ip) => buffer.extend_from_slice(&ip.octets()),
+            RData::Aaaa(ip) => buffer.extend_from_slice(&ip.octets()),
+            RData::Cname(nom) | RData::Ns(nom) | RData::Ptr(nom) => ecrire_nom(buffer, nom),
+            RData::Mx { preference, exchange } => {
+                buffer.write_u16::<BigEndian>(*preference).unwrap();
+                ecrire_nom(buffer, exchange);
+            }
+            RData::Txt(textes) => {
+                for texte in textes {
+                    // Une chaîne TXT est limitée à 255 octets : on découpe les textes plus longs
+                    if texte.is_empty() {
+                        buffer.push(0);
+                    }
+                    for morceau in texte.chunks(255) {
+                        buffer.push(morceau.len() as u8);
+                        buffer.extend_from_slice(morceau);
+                    }
+                }
+            }
+            RData::Soa { mname, rname, serial, refresh, retry, expire, minimum } => {
+                ecrire_nom(buffer, mname);
+                ecrire_nom(buffer, rname);
+                for valeur in [serial, refresh, retry, expire, minimum] {
+                    buffer.write_u32::<BigEndian>(*valeur).unwrap();
+                }
+            }
+            RData::Srv { priority, weight, port, target } => {
+                buffer.write_u16::<BigEndian>(*priority).unwrap();
+                buffer.write_u16::<BigEndian>(*weight).unwrap();
+                buffer.write_u16::<BigEndian>(*port).unwrap();
+                // La RFC 2782 interdit la compression de la cible SRV
+                DnsQuestion::write_domain_name(target, buffer);
+            }
+            RData::Unknown(brut) => buffer.extend_from_slice(brut),
+        }
+    }
+}
+
+impl fmt::Display for RData {
+    /// Affiche les données au format des fichiers de zone
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RData::A(ip) => write!(f, "{}", ip),
+            RData::Aaaa(ip) => write!(f, "{}", ip),
+            RData::Cname(nom) | RData::Ns(nom) | RData::Ptr(nom) => write!(f, "{}", fqdn(nom)),
+            RData::Mx { preference, exchange } => write!(f, "{} {}", preference, fqdn(exchange)),
+            RData::Txt(textes) => {
+                let textes: Vec<String> = textes
+                    .iter()
+                    .map(|texte| format!("\"{}\"", echapper_chaine(texte)))
+                    .collect();
+                write!(f, "{}", textes.join(" "))
+            }
+            RData::Soa { mname, rname, serial, refresh, retry, expire, minimum } => write!(
+                f,
+                "{} {} {} {} {} {} {}",
+                fqdn(mname), fqdn(rname), serial, refresh, retry, expire, minimum
+            ),
+            RData::Srv { priority, weight, port, target } => {
+                write!(f, "{} {} {} {}", priority, weight, port, fqdn(target))
+            }
+            RData::Unknown(brut) => {
+                // Format générique de la RFC 3597
+                write!(f, "\\# {}", brut.len())?;
+                if !brut.is_empty() {
+                    write!(f, " ")?;
+                    for octet in brut {
+                        write!(f, "{:02x}", octet)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Chaîne de caractères au format des fichiers de zone (RFC 1035 §5.1) : `"` et `\` sont
+/// précédés d'un `\`, les octets non imprimables sont écrits `\DDD`
+fn echapper_chaine(octets: &[u8]) -> String {
+    let mut texte = String::with_capacity(octets.len());
+    for &octet in octets {
+        match octet {
+            b'"' | b'\\' => {
+                texte.push('\\');
+                texte.push(octet as char);
+            }
+            0x20..=0x7e => texte.push(octet as char),
+            _ => texte.push_str(&format!("\\{:03}", octet)),
+        }
+    }
+    texte
+}
+
+/// Nom textuel d'un type DNS (ex : 28 -> "AAAA")
+pub fn type_to_string(rtype: u16) -> String {
+    match rtype {
+        DNS_TYPE_A => "A".to_string(),
+        DNS_TYPE_NS => "NS".to_string(),
+        DNS_TYPE_CNAME => "CNAME".to_string(),
+        DNS_TYPE_SOA => "SOA".to_string(),
+        DNS_TYPE_PTR => "PTR".to_string(),
+        DNS_TYPE_MX => "MX".to_string(),
+        DNS_TYPE_TXT => "TXT".to_string(),
+        DNS_TYPE_AAAA => "AAAA".to_string(),
+        DNS_TYPE_SRV => "SRV".to_string(),
+        DNS_TYPE_OPT => "OPT".to_string(),
+        DNS_TYPE_TSIG => "TSIG".to_string(),
+        DNS_TYPE_IXFR => "IXFR".to_string(),
+        DNS_TYPE_AXFR => "AXFR".to_string(),
+        DNS_TYPE_ANY => "ANY".to_string(),
+        _ => format!("TYPE{}", rtype),
+    }
+}
+
+/// Type DNS correspondant à un nom textuel, insensible à la casse (accepte aussi "TYPEnnn")
+pub fn type_from_str(nom: &str) -> Option<u16> {
+    let nom = nom.to_ascii_uppercase();
+    match nom.as_str() {
+        "A" => Some(DNS_TYPE_A),
+        "NS" => Some(DNS_TYPE_NS),
+        "CNAME" => Some(DNS_TYPE_CNAME),
+        "SOA" => Some(DNS_TYPE_SOA),
+        "PTR" => Some(DNS_TYPE_PTR),
+        "MX" => Some(DNS_TYPE_MX),
+        "TXT" => Some(DNS_TYPE_TXT),
+        "AAAA" => Some(DNS_TYPE_AAAA),
+        "SRV" => Some(DNS_TYPE_SRV),
+        "OPT" => Some(DNS_TYPE_OPT),
+        "TSIG" => Some(DNS_TYPE_TSIG),
+        "IXFR" => Some(DNS_TYPE_IXFR),
+        "AXFR" => Some(DNS_TYPE_AXFR),
+        "ANY" => Some(DNS_TYPE_ANY),
+        _ => nom.strip_prefix("TYPE").and_then(|numero| numero.parse().ok()),
+    }
+}
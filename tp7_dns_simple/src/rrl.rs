@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Instant;
+
+use crate::{Cidr, DnsMessage, Rcode, DNS_TYPE_SOA};
+
+/// Nombre de comptes suivis au-delà duquel les comptes revenus à leur plein sont oubliés
+const MAX_COMPTES: usize = 10000;
+
+/// Paramètres de la limitation de débit. Un débit de 0 désactive la limite correspondante.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitConfig {
+    /// Réponses identiques (même nom, même type) par seconde vers un même préfixe client
+    pub responses_per_second: u32,
+    /// Réponses NXDOMAIN ou d'erreur par seconde vers un même préfixe client
+    pub errors_per_second: u32,
+    /// Requêtes par seconde acceptées d'une même adresse, toutes réponses confondues
+    pub queries_per_second: u32,
+    /// Une réponse limitée sur `slip` est remplacée par une réponse tronquée (TC) ; 0 pour toutes les ignorer
+    pub slip: u32,
+    /// Dette maximale d'un compte, en secondes de débit : durée de la limitation après un excès
+    pub window: u32,
+    /// Longueur des préfixes regroupant les clients IPv4
+    pub ipv4_prefix: u8,
+    /// Longueur des préfixes regroupant les clients IPv6
+    pub ipv6_prefix: u8,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            responses_per_second: 0,
+            errors_per_second: 0,
+            queries_per_second: 0,
+            slip: 2,
+            window: 15,
+            ipv4_prefix: 24,
+            ipv6_prefix: 56,
+        }
+    }
+}
+
+/// Sort d'une réponse soumise à la limitation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    /// Réponse envoyée normalement
+    Send,
+    /// Réponse remplacée par une réponse vide tronquée, qui renvoie un vrai client vers TCP
+    Slip,
+    /// Réponse ignorée
+    Drop,
+}
+
+/// Compteurs de la limitation de débit
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RateLimitStats {
+    /// Réponses envoyées sans limitation
+    pub allowed: u64,
+    /// Réponses remplacées par une réponse tronquée
+    pub slipped: u64,
+    /// Réponses ignorées
+    pub dropped: u64,
+    /// Requêtes ignorées car le client a dépassé son quota
+    pub over_quota: u64,
+    /// Comptes actuellement suivis
+    pub tracked: usize,
+}
+
+/// Catégorie de réponse : les réponses d'une même catégorie partagent un compte
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ResponseKind {
+    Answer,
+    NoData,
+    NxDomain,
+    Error,
+}
+
+/// Compte de limitation : préfixe client et réponse type
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct AccountKey {
+    prefix: IpAddr,
+    kind: ResponseKind,
+    name: String,
+    qtype: u16,
+}
+
+/// Seau à jetons, rempli au débit autorisé
+#[derive(Debug, Clone)]
+struct Bucket {
+    balance: f64,
+    updated: Instant,
+    limited: u64,
+}
+
+impl Bucket {
+    fn new(rate: u32, now: Instant) -> Self {
+        Bucket { balance: rate as f64, updated: now, limited: 0 }
+    }
+
+    /// Solde à l'instant `now`, plafonné au débit (une seconde de rafale)
+    fn balance_at(&self, rate: u32, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        (self.balance + elapsed * rate as f64).min(rate as f64)
+    }
+
+    /// Consomme un jeton ; retourne `false` si le compte est épuisé
+    fn take(&mut self, rate: u32, window: u32, now: Instant) -> bool {
+        self.balance = self.balance_at(rate, now) - 1.0;
+        self.updated = now;
+        if self.balance >= 0.0 {
+            return true;
+        }
+        // La dette est bornée pour qu'un client limité retrouve ses réponses après `window` secondes
+        self.balance = self.balance.max(-(rate as f64) * window as f64);
+        self.limited += 1;
+        false
+    }
+}
+
+/// Limitation du débit des réponses (RRL) et quotas de requêtes par client, contre l'usage du
+/// serveur comme amplificateur : les réponses identiques vers un même préfixe client au-delà du
+/// débit configuré sont ignorées ou remplacées par une réponse tronquée.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    responses: HashMap<AccountKey, Bucket>,
+    queries: HashMap<IpAddr, Bucket>,
+    allowed: u64,
+    slipped: u64,
+    dropped: u64,
+    over_quota: u64,
+}
+
+impl RateLimiter {
+    /// Crée un limiteur avec les paramètres donnés
+    pub fn new(config: RateLimitConfig) -> Self {
+        RateLimiter {
+            config,
+            responses: HashMap::new(),
+            queries: HashMap::new(),
+            allowed: 0,
+            slipped: 0,
+            dropped: 0,
+            over_quota: 0,
+        }
+    }
+
+    /// Paramètres du limiteur
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Indique si une requête de `client` entre dans son quota
+    pub fn allow_query(&mut self, client: IpAddr) -> bool {
+        self.allow_query_at(client, Instant::now())
+    }
+
+    /// Variante de `allow_query` à un instant donné
+    pub fn allow_query_at(&mut self, client: IpAddr, now: Instant) -> bool {
+        let rate = self.config.queries_per_second;
+        if rate == 0 {
+            return true;
+        }
+        self.prune(now);
+        let bucket = self.queries.entry(client).or_insert_with(|| Bucket::new(rate, now));
+        let allowed = bucket.take(rate, self.config.window, now);
+        if !allowed {
+            self.over_quota += 1;
+        }
+        allowed
+    }
+
+    /// Décide du sort de `response`, destinée à `client`
+    pub fn check_response(&mut self, client: IpAddr, response: &DnsMessage) -> RateLimitDecision {
+        self.check_response_at(client, response, Instant::now())
+    }
+
+    /// Variante de `check_response` à un instant donné
+    pub fn check_response_at(&mut self, client: IpAddr, response: &DnsMessage, now: Instant) -> RateLimitDecision {
+        let key = self.account(client, response);
+        let rate = match key.kind {
+            ResponseKind::Answer | ResponseKind::NoData => self.config.responses_per_second,
+            ResponseKind::NxDomain | ResponseKind::Error => self.config.errors_per_second,
+        };
+        if rate == 0 {
+            self.allowed += 1;
+            return RateLimitDecision::Send;
+        }
+
+        self.prune(now);
+        let bucket = self.responses.entry(key).or_insert_with(|| Bucket::new(rate, now));
+        if bucket.take(rate, self.config.window, now) {
+            self.allowed += 1;
+            RateLimitDecision::Send
+        } else if self.config.slip > 0 && bucket.limited.is_multiple_of(self.config.slip as u64) {
+            self.slipped += 1;
+            RateLimitDecision::Slip
+        } else {
+            self.dropped += 1;
+            RateLimitDecision::Drop
+        }
+    }
+
+    /// Compteurs de réponses envoyées, tronquées et ignorées
+    pub fn stats(&self) -> RateLimitStats {
+        RateLimitStats {
+            allowed: self.allowed,
+            slipped: self.slipped,
+            dropped: self.dropped,
+            over_quota: self.over_quota,
+            tracked: self.responses.len() + self.queries.len(),
+        }
+    }
+
+    /// Compte d'une réponse. Les NXDOMAIN sont regroupés par zone, pour qu'une rafale de noms
+    /// aléatoires ne contourne pas la limite, et les erreurs par préfixe client seulement.
+    fn account(&self, client: IpAddr, response: &DnsMessage) -> AccountKey {
+        let question = response.questions.first();
+        let qname = question.map(|q| q.qname.trim_end_matches('.').to_ascii_lowercase()).unwrap_or_default();
+        let qtype = question.map_or(0, |q| q.qtype);
+        let zone = response
+            .authorities
+            .iter()
+            .find(|record| record.rtype == DNS_TYPE_SOA)
+            .map(|soa| soa.name.trim_end_matches('.').to_ascii_lowercase());
+
+        let (kind, name, qtype) = match response.rcode() {
+            Rcode::NoError if !response.answers.is_empty() => (ResponseKind::Answer, qname, qtype),
+            Rcode::NoError => (ResponseKind::NoData, qname, qtype),
+            Rcode::NxDomain => (ResponseKind::NxDomain, zone.unwrap_or(qname), 0),
+            _ => (ResponseKind::Error, String::new(), 0),
+        };
+        AccountKey { prefix: self.prefix(client), kind, name, qtype }
+    }
+
+    /// Préfixe regroupant `client` avec ses voisins
+    fn prefix(&self, client: IpAddr) -> IpAddr {
+        let longueur = if client.is_ipv4() { self.config.ipv4_prefix } else { self.config.ipv6_prefix };
+        Cidr::new(client, longueur).network()
+    }
+
+    /// Oublie les comptes revenus à leur plein lorsque la table devient trop grande
+    fn prune(&mut self, now: Instant) {
+        if self.responses.len() + self.queries.len() < MAX_COMPTES {
+            return;
+        }
+        let config = self.config;
+        self.responses.retain(|key, bucket| {
+            let rate = match key.kind {
+                ResponseKind::Answer | ResponseKind::NoData => config.responses_per_second,
+                ResponseKind::NxDomain | ResponseKind::Error => config.errors_per_second,
+            };
+            bucket.balance_at(rate, now) < rate as f64
+        });
+        self.queries.retain(|_, bucket| bucket.balance_at(config.queries_per_second, now) < config.queries_per_second as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DnsAnswer, RData, DNS_TYPE_A};
+    use std::time::Duration;
+
+    fn reponse(nom: &str, rcode: Rcode) -> DnsMessage {
+        let requete = DnsMessage::new_query(1, nom.to_string(), DNS_TYPE_A).unwrap();
+        let mut reponse = DnsMessage::new_response(&requete);
+        reponse.set_rcode(rcode);
+        if rcode == Rcode::NoError {
+            reponse.add_answer(DnsAnswer::new(nom.to_string(), 60, RData::A("10.0.0.1".parse().unwrap())));
+        }
+        reponse
+    }
+
+    fn client(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn test_limite_reponses_identiques_par_prefixe() {
+        let config = RateLimitConfig { responses_per_second: 2, slip: 2, ..RateLimitConfig::default() };
+        let mut limiteur = RateLimiter::new(config);
+        let maintenant = Instant::now();
+        let www = reponse("www.exemple.com", Rcode::NoError);
+
+        let decisions: Vec<_> = (0..5).map(|_| limiteur.check_response_at(client("10.0.0.1"), &www, maintenant)).collect();
+        use RateLimitDecision::*;
+        assert_eq!(decisions, vec![Send, Send, Drop, Slip, Drop]);
+
+        // Même préfixe /24 : même compte ; autre préfixe ou autre nom : compte distinct
+        assert_eq!(limiteur.check_response_at(client("10.0.0.200"), &www, maintenant), Slip);
+        assert_eq!(limiteur.check_response_at(client("10.0.1.1"), &www, maintenant), Send);
+        let mail = reponse("mail.exemple.com", Rcode::NoError);
+        assert_eq!(limiteur.check_response_at(client("10.0.0.1"), &mail, maintenant), Send);
+
+        let stats = limiteur.stats();
+        assert_eq!((stats.allowed, stats.slipped, stats.dropped), (4, 2, 2));
+
+        // La dette se résorbe au débit autorisé
+        let plus_tard = maintenant + Duration::from_secs(3);
+        assert_eq!(limiteur.check_response_at(client("10.0.0.1"), &www, plus_tard), Send);
+    }
+
+    #[test]
+    fn test_nxdomain_regroupes_par_zone() {
+        let config = RateLimitConfig { errors_per_second: 1, slip: 0, ..RateLimitConfig::default() };
+        let mut limiteur = RateLimiter::new(config);
+        let maintenant = Instant::now();
+        let soa = DnsAnswer::new("exemple.com".to_string(), 60, RData::Soa {
+            mname: "ns.exemple.com".to_string(),
+            rname: "admin.exemple.com".to_string(),
+            serial: 1,
+            refresh: 3600,
+            retry: 600,
+            expire: 86400,
+            minimum: 60,
+        });
+
+        let mut premiere = reponse("a1.exemple.com", Rcode::NxDomain);
+        premiere.add_authority(soa.clone());
+        let mut seconde = reponse("b2.exemple.com", Rcode::NxDomain);
+        seconde.add_authority(soa);
+        assert_eq!(limiteur.check_response_at(client("192.0.2.1"), &premiere, maintenant), RateLimitDecision::Send);
+        assert_eq!(limiteur.check_response_at(client("192.0.2.1"), &seconde, maintenant), RateLimitDecision::Drop);
+
+        // Les réponses positives ne sont pas limitées par le débit d'erreurs
+        let www = reponse("www.exemple.com", Rcode::NoError);
+        assert_eq!(limiteur.check_response_at(client("192.0.2.1"), &www, maintenant), RateLimitDecision::Send);
+    }
+
+    #[test]
+    fn test_quota_de_requetes_par_client() {
+        let config = RateLimitConfig { queries_per_second: 1, window: 2, ..RateLimitConfig::default() };
+        let mut limiteur = RateLimiter::new(config);
+        let maintenant = Instant::now();
+
+        assert!(limiteur.allow_query_at(client("2001:db8::1"), maintenant));
+        assert!(!limiteur.allow_query_at(client("2001:db8::1"), maintenant));
+        // Le quota est propre à chaque adresse, même dans un préfixe commun
+        assert!(limiteur.allow_query_at(client("2001:db8::2"), maintenant));
+        assert_eq!(limiteur.stats().over_quota, 1);
+
+        assert!(limiteur.allow_query_at(client("2001:db8::1"), maintenant + Duration::from_secs(3)));
+    }
+}
@@ -1,53 +1,842 @@
 use std::collections::HashMap;
-use std::net::SocketAddr;
-use tokio::net::UdpSocket;
-use tp7_dns::{DnsMessage, DnsAnswer, DNS_TYPE_A};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+use tp7_dns::{read_tcp_message, serial_newer, transfer_messages, type_to_string, write_tcp_message, CacheStats, DnsAnswer, DnsCache, DnsHeader, DnsMessage, Edns, LookupStatus, Opcode, RData, Rcode, RecordTable, Transfer, TsigContext, TsigKey, TsigKeyring, Update, ZoneDiff, ZoneError, ZoneJournal};
+use tp7_dns::{sign_request, unix_time, verify_request, Metrics, QueryLog, QueryLogEntry, RateLimitConfig, RateLimitDecision, RateLimitStats, RateLimiter};
+use tp7_dns::{DNS_TYPE_AXFR, DNS_TYPE_IXFR, EDNS_UDP_PAYLOAD};
+
+/// Intervalle de vérification des modifications du fichier de zone
+const INTERVALLE_SURVEILLANCE: Duration = Duration::from_secs(2);
+
+/// Délai d'attente par défaut d'une réponse du résolveur amont
+const DELAI_AMONT_PAR_DEFAUT: Duration = Duration::from_secs(2);
+
+/// Durée après laquelle une connexion TCP inactive est fermée
+const DELAI_INACTIVITE_TCP: Duration = Duration::from_secs(10);
+
+/// Nombre de réponses amont conservées en cache par défaut
+const CAPACITE_CACHE_PAR_DEFAUT: usize = 1000;
+
+/// Nombre maximal de requêtes traitées simultanément par défaut
+const LIMITE_CONCURRENCE_PAR_DEFAUT: usize = 64;
+
+/// Durée maximale d'un transfert de zone depuis le primaire
+const DELAI_TRANSFERT: Duration = Duration::from_secs(30);
+
+/// Délai avant un nouvel essai de transfert lorsque la zone n'a encore jamais été reçue
+const DELAI_RELANCE_SECONDAIRE: Duration = Duration::from_secs(60);
+
+/// Taille par défaut du journal des requêtes avant rotation (10 Mo)
+const TAILLE_JOURNAL_PAR_DEFAUT: u64 = 10 * 1024 * 1024;
+
+/// Nombre d'archives du journal des requêtes conservées par défaut
+const JOURNAUX_CONSERVES_PAR_DEFAUT: usize = 5;
+
+/// Taille maximale de l'en-tête d'une requête HTTP adressée au point d'accès des métriques
+const TAILLE_MAX_REQUETE_HTTP: usize = 8192;
+
+/// Intervalle d'affichage des statistiques lorsque la limitation de débit est active
+const INTERVALLE_STATISTIQUES: Duration = Duration::from_secs(60);
+
+/// Aide affichée en cas d'arguments invalides
+const USAGE: &str = "Usage: serveur_dns [--zone FICHIER] [--adresse ADRESSE] [--concurrence N] \
+                     [--amont ADRESSE] [--delai-amont MS] [--cache N] [--ptr] \
+                     [--transfert-autorise IP]... [--secondaire ZONE@ADRESSE]... \
+                     [--mise-a-jour-autorisee IP]... [--sauvegarde FICHIER] [--tsig NOM:SECRET_BASE64]... \
+                     [--rrl N] [--rrl-erreurs N] [--rrl-glissement N] [--quota N] \
+                     [--journal-requetes FICHIER] [--journal-taille OCTETS] [--journal-conserves N] \
+                     [--metriques ADRESSE]";
+
+/// Zone servie lorsqu'aucun fichier n'est passé en argument
+const ZONE_PAR_DEFAUT: &str = include_str!("../zones/lab.zone");
+
+/// Options de la ligne de commande du serveur
+#[derive(Debug)]
+pub struct ConfigServeur {
+    /// Adresse d'écoute UDP et TCP
+    pub adresse: String,
+    /// Fichier de zone à charger (zone de démonstration si absent)
+    pub fichier_zone: Option<PathBuf>,
+    /// Nombre maximal de requêtes en cours de traitement
+    pub limite_concurrence: usize,
+    /// Résolveur vers lequel transférer les noms inconnus (mode transfert)
+    pub amont: Option<SocketAddr>,
+    /// Délai d'attente d'une réponse du résolveur amont
+    pub delai_amont: Duration,
+    /// Nombre de réponses amont mises en cache (0 pour désactiver)
+    pub capacite_cache: usize,
+    /// Génère les PTR de résolution inverse à partir des A et AAAA
+    pub synthese_ptr: bool,
+    /// Adresses des secondaires autorisés à transférer nos zones (AXFR/IXFR)
+    pub transferts_autorises: Vec<IpAddr>,
+    /// Zones reprises d'un serveur primaire : zone, adresse du primaire et clé TSIG éventuelle
+    pub secondaires: Vec<(String, SocketAddr, Option<String>)>,
+    /// Adresses autorisées à modifier nos zones par mise à jour dynamique (UPDATE)
+    pub mises_a_jour_autorisees: Vec<IpAddr>,
+    /// Fichier où enregistrer les zones modifiées par UPDATE, rechargé au démarrage s'il existe.
+    /// Le fichier de zone n'est jamais réécrit.
+    pub fichier_sauvegarde: Option<PathBuf>,
+    /// Clés TSIG acceptées : une requête signée par l'une d'elles peut transférer et modifier nos zones
+    pub cles_tsig: Vec<TsigKey>,
+    /// Limitation du débit des réponses UDP et quotas de requêtes par client, si l'une est demandée
+    pub limitation: Option<RateLimitConfig>,
+    /// Journal des requêtes au format JSON Lines (désactivé si absent)
+    pub journal_requetes: Option<PathBuf>,
+    /// Taille du journal des requêtes au-delà de laquelle il est archivé
+    pub taille_journal: u64,
+    /// Nombre d'archives du journal des requêtes conservées
+    pub journaux_conserves: usize,
+    /// Adresse HTTP exposant les métriques au format Prometheus (désactivé si absente)
+    pub metriques: Option<SocketAddr>,
+}
+
+impl ConfigServeur {
+    /// Lit la configuration depuis les arguments de la ligne de commande (voir `USAGE`)
+    pub fn depuis_arguments(arguments: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut config = ConfigServeur {
+            adresse: "127.0.0.1:8053".to_string(),
+            fichier_zone: None,
+            limite_concurrence: LIMITE_CONCURRENCE_PAR_DEFAUT,
+            amont: None,
+            delai_amont: DELAI_AMONT_PAR_DEFAUT,
+            capacite_cache: CAPACITE_CACHE_PAR_DEFAUT,
+            synthese_ptr: false,
+            transferts_autorises: Vec::new(),
+            secondaires: Vec::new(),
+            mises_a_jour_autorisees: Vec::new(),
+            fichier_sauvegarde: None,
+            cles_tsig: Vec::new(),
+            limitation: None,
+            journal_requetes: None,
+            taille_journal: TAILLE_JOURNAL_PAR_DEFAUT,
+            journaux_conserves: JOURNAUX_CONSERVES_PAR_DEFAUT,
+            metriques: None,
+        };
+
+        let mut arguments = arguments;
+        while let Some(argument) = arguments.next() {
+            let mut valeur = || arguments.next().ok_or_else(|| format!("valeur manquante pour {}", argument));
+            match argument.as_str() {
+                "--zone" => config.fichier_zone = Some(PathBuf::from(valeur()?)),
+                "--adresse" => config.adresse = valeur()?,
+                "--concurrence" => {
+                    config.limite_concurrence = valeur()?
+                        .parse()
+                        .ok()
+                        .filter(|&limite| limite > 0)
+                        .ok_or("--concurrence attend un entier strictement positif")?;
+                }
+                "--amont" => {
+                    config.amont = Some(valeur()?.parse().map_err(|_| "--amont attend une adresse IP:port")?);
+                }
+                "--delai-amont" => {
+                    let millisecondes: u64 = valeur()?.parse().map_err(|_| "--delai-amont attend un nombre de millisecondes")?;
+                    config.delai_amont = Duration::from_millis(millisecondes);
+                }
+                "--cache" => {
+                    config.capacite_cache = valeur()?.parse().map_err(|_| "--cache attend un nombre d'entrées")?;
+                }
+                "--ptr" => config.synthese_ptr = true,
+                "--transfert-autorise" => {
+                    let adresse = valeur()?.parse().map_err(|_| "--transfert-autorise attend une adresse IP")?;
+                    config.transferts_autorises.push(adresse);
+                }
+                "--secondaire" => {
+                    let valeur = valeur()?;
+                    let (zone, primaire) = valeur.split_once('@').ok_or("--secondaire attend ZONE@IP:port[,CLE]")?;
+                    let (primaire, cle) = match primaire.split_once(',') {
+                        Some((primaire, cle)) => (primaire, Some(cle.to_string())),
+                        None => (primaire, None),
+                    };
+                    let primaire = primaire.parse().map_err(|_| "--secondaire attend ZONE@IP:port[,CLE]")?;
+                    config.secondaires.push((zone.to_string(), primaire, cle));
+                }
+                "--sauvegarde" => config.fichier_sauvegarde = Some(PathBuf::from(valeur()?)),
+                "--tsig" => config.cles_tsig.push(TsigKey::parse(&valeur()?)?),
+                "--mise-a-jour-autorisee" => {
+                    let adresse = valeur()?.parse().map_err(|_| "--mise-a-jour-autorisee attend une adresse IP")?;
+                    config.mises_a_jour_autorisees.push(adresse);
+                }
+                "--rrl" | "--rrl-erreurs" | "--rrl-glissement" | "--quota" => {
+                    let nombre: u32 = valeur()?.parse().map_err(|_| format!("{} attend un entier", argument))?;
+                    let limitation = config.limitation.get_or_insert_with(RateLimitConfig::default);
+                    match argument.as_str() {
+                        "--rrl" => limitation.responses_per_second = nombre,
+                        "--rrl-erreurs" => limitation.errors_per_second = nombre,
+                        "--rrl-glissement" => limitation.slip = nombre,
+                        _ => limitation.queries_per_second = nombre,
+                    }
+                }
+                "--journal-requetes" => config.journal_requetes = Some(PathBuf::from(valeur()?)),
+                "--journal-taille" => {
+                    config.taille_journal = valeur()?
+                        .parse()
+                        .ok()
+                        .filter(|&taille| taille > 0)
+                        .ok_or("--journal-taille attend un nombre d'octets strictement positif")?;
+                }
+                "--journal-conserves" => {
+                    config.journaux_conserves = valeur()?.parse().map_err(|_| "--journal-conserves attend un nombre de fichiers")?;
+                }
+                "--metriques" => {
+                    config.metriques = Some(valeur()?.parse().map_err(|_| "--metriques attend une adresse IP:port")?);
+                }
+                _ => return Err(format!("argument inconnu: {}", argument)),
+            }
+        }
+
+        // Sauf mention contraire, les erreurs sont limitées au même débit que les réponses
+        if let Some(limitation) = config.limitation.as_mut() {
+            if limitation.errors_per_second == 0 {
+                limitation.errors_per_second = limitation.responses_per_second;
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Charge et valide la table des enregistrements depuis le fichier de zone configuré,
+    /// ou depuis le fichier de sauvegarde des mises à jour dynamiques s'il existe déjà
+    pub fn charger_zone(&self) -> Result<RecordTable, ZoneError> {
+        let sauvegarde = self.fichier_sauvegarde.as_ref().filter(|chemin| chemin.exists());
+        let table = match sauvegarde.or(self.fichier_zone.as_ref()) {
+            Some(chemin) => RecordTable::load(chemin)?,
+            None => RecordTable::parse(ZONE_PAR_DEFAUT, None)?,
+        };
+        table.validate()?;
+        Ok(table)
+    }
+}
+
+/// Résolveur amont utilisé en mode transfert
+#[derive(Debug, Clone, Copy)]
+struct ResolveurAmont {
+    adresse: SocketAddr,
+    delai: Duration,
+}
+
+/// Échec d'une mise à jour dynamique
+enum EchecMiseAJour {
+    /// Prérequis non satisfaits ou mise à jour mal formée
+    Rcode(Rcode),
+    /// La zone modifiée n'est plus valide
+    Zone(ZoneError),
+}
+
+impl From<ZoneError> for EchecMiseAJour {
+    fn from(erreur: ZoneError) -> Self {
+        EchecMiseAJour::Zone(erreur)
+    }
+}
+
+/// Zone reprise d'un serveur primaire par transfert
+#[derive(Debug, Clone)]
+struct ZoneSecondaire {
+    apex: String,
+    primaire: SocketAddr,
+    /// Clé signant les demandes de transfert
+    cle: Option<TsigKey>,
+}
+
+/// Compteurs d'activité du serveur
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StatistiquesServeur {
+    /// Messages reçus, en UDP et en TCP
+    pub requetes: u64,
+    /// Utilisation du cache des réponses amont, s'il est actif
+    pub cache: Option<CacheStats>,
+    /// Réponses limitées et requêtes hors quota, si la limitation est active
+    pub limitation: Option<RateLimitStats>,
+}
 
 /// Structure représentant le serveur DNS simple
 pub struct ServeurDns {
-    socket: UdpSocket,
-    /// Base de données simple des domaines -> IP
-    domaines: HashMap<String, [u8; 4]>,
+    socket: Arc<UdpSocket>,
+    /// Écoute TCP sur le même port, pour les réponses trop grandes pour UDP
+    ecoute_tcp: TcpListener,
+    /// Limite le nombre de requêtes traitées en parallèle
+    concurrence: Arc<Semaphore>,
+    /// Résolveur amont pour les noms absents de la table (mode transfert)
+    amont: Option<ResolveurAmont>,
+    /// Réponses de l'amont conservées selon leur TTL
+    cache: Option<Mutex<DnsCache>>,
+    /// Enregistrements tels que chargés du fichier de zone, puis modifiés par les mises à jour
+    /// et les transferts : c'est cette table qui est enregistrée, sans les PTR générés
+    source: RwLock<Arc<RecordTable>>,
+    /// Enregistrements servis, dérivés de `source` (PTR inverses compris) et remplacés
+    /// d'un bloc à chaque modification
+    domaines: RwLock<Arc<RecordTable>>,
+    /// Table servie aux clients de chaque vue, recalculée à chaque remplacement de `domaines`
+    vues: RwLock<HashMap<String, Arc<RecordTable>>>,
+    /// Les PTR inverses sont générés à chaque chargement de la zone
+    synthese_ptr: bool,
+    /// Modifications successives des zones, pour les transferts incrémentaux
+    journal: Mutex<ZoneJournal>,
+    /// Secondaires autorisés à transférer nos zones
+    transferts_autorises: Vec<IpAddr>,
+    /// Zones reprises d'un primaire, tenues à jour à chaque intervalle de rafraîchissement
+    secondaires: Vec<ZoneSecondaire>,
+    /// Clients autorisés à modifier nos zones par mise à jour dynamique
+    mises_a_jour_autorisees: Vec<IpAddr>,
+    /// Fichier dans lequel les mises à jour dynamiques sont enregistrées ; le verrou
+    /// garantit que la dernière version écrite est la dernière version servie
+    sauvegarde: Mutex<Option<PathBuf>>,
+    /// Clés TSIG acceptées pour les requêtes signées
+    cles_tsig: TsigKeyring,
+    /// Limitation du débit des réponses UDP et quotas par client
+    limiteur: Option<Mutex<RateLimiter>>,
+    /// Nombre de messages reçus
+    requetes: AtomicU64,
+    /// Journal structuré des requêtes et des réponses
+    journal_requetes: Option<Mutex<QueryLog>>,
+    /// Compteurs par type, par code de réponse, de latence et de paquets ignorés
+    metriques: Mutex<Metrics>,
 }
 
 impl ServeurDns {
-    /// Crée un nouveau serveur DNS
-    pub async fn new(adresse: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    /// Crée un nouveau serveur DNS servant les enregistrements de `domaines`
+    pub async fn new(adresse: &str, domaines: RecordTable) -> Result<Self, Box<dyn std::error::Error>> {
         let socket = UdpSocket::bind(adresse).await?;
+        // Même port qu'en UDP, y compris lorsque le port a été choisi par le système
+        let ecoute_tcp = TcpListener::bind(socket.local_addr()?).await?;
         
-        // Initialise quelques domaines prédéfinis pour les tests
-        let mut domaines = HashMap::new();
-        domaines.insert("exemple.com".to_string(), [192, 168, 1, 100]);
-        domaines.insert("test.local".to_string(), [127, 0, 0, 1]);
-        domaines.insert("serveur.esgi".to_string(), [10, 0, 0, 50]);
-        domaines.insert("www.exemple.com".to_string(), [192, 168, 1, 101]);
-        
-        println!("Serveur DNS démarré sur {}", adresse);
-        println!("Domaines configurés :");
-        for (domaine, ip) in &domaines {
-            println!("   {} -> {}.{}.{}.{}", domaine, ip[0], ip[1], ip[2], ip[3]);
+        println!("Serveur DNS démarré sur {} (UDP et TCP)", adresse);
+        println!("Enregistrements configurés ({}) :", domaines.len());
+        for record in domaines.records() {
+            println!("   {}", record);
+        }
+        for vue in domaines.views() {
+            let reseaux: Vec<String> = vue.networks.iter().map(|reseau| reseau.to_string()).collect();
+            println!("Vue {} ({}) : {} enregistrement(s) propre(s)", vue.name, reseaux.join(", "), vue.records().count());
         }
+        let vues = Self::preparer_vues(&domaines, false);
+        let domaines = Arc::new(domaines);
         
         Ok(ServeurDns {
-            socket,
-            domaines,
+            socket: Arc::new(socket),
+            ecoute_tcp,
+            concurrence: Arc::new(Semaphore::new(LIMITE_CONCURRENCE_PAR_DEFAUT)),
+            amont: None,
+            cache: None,
+            domaines: RwLock::new(Arc::clone(&domaines)),
+            source: RwLock::new(domaines),
+            vues: RwLock::new(vues),
+            synthese_ptr: false,
+            journal: Mutex::new(ZoneJournal::new()),
+            transferts_autorises: Vec::new(),
+            secondaires: Vec::new(),
+            mises_a_jour_autorisees: Vec::new(),
+            sauvegarde: Mutex::new(None),
+            cles_tsig: TsigKeyring::new(),
+            limiteur: None,
+            requetes: AtomicU64::new(0),
+            journal_requetes: None,
+            metriques: Mutex::new(Metrics::new()),
         })
     }
 
-    /// Démarre l'écoute des requêtes DNS
-    pub async fn demarrer(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut buffer = vec![0u8; 512]; // Taille standard DNS
+    /// Définit le nombre maximal de requêtes traitées simultanément
+    pub fn avec_limite_concurrence(mut self, limite: usize) -> Self {
+        self.concurrence = Arc::new(Semaphore::new(limite));
+        self
+    }
+
+    /// Active le mode transfert : les noms inconnus sont résolus par `adresse`
+    pub fn avec_amont(mut self, adresse: SocketAddr, delai: Duration) -> Self {
+        println!("Mode transfert actif vers {} (délai {} ms)", adresse, delai.as_millis());
+        self.amont = Some(ResolveurAmont { adresse, delai });
+        self
+    }
+
+    /// Met en cache jusqu'à `capacite` réponses obtenues de l'amont
+    pub fn avec_cache(mut self, capacite: usize) -> Self {
+        self.cache = (capacite > 0).then(|| Mutex::new(DnsCache::new(capacite)));
+        self
+    }
+
+    /// Génère les PTR de résolution inverse des A et AAAA servis, maintenant et à chaque rechargement
+    pub fn avec_synthese_ptr(mut self) -> Self {
+        self.synthese_ptr = true;
+        let mut table = RecordTable::clone(&self.source.read().unwrap());
+        println!("{} PTR générés pour la résolution inverse", table.synthesize_ptr());
+        self.vues = RwLock::new(Self::preparer_vues(&table, true));
+        self.domaines = RwLock::new(Arc::new(table));
+        self
+    }
+
+    /// Autorise les adresses données à transférer nos zones (AXFR et IXFR sur TCP)
+    pub fn avec_transferts_autorises(mut self, adresses: Vec<IpAddr>) -> Self {
+        self.transferts_autorises = adresses;
+        self
+    }
+
+    /// Reprend la zone `apex` du serveur `primaire`, au démarrage puis à chaque rafraîchissement.
+    /// Les demandes de transfert sont signées avec `cle` si elle est fournie.
+    pub fn avec_zone_secondaire(mut self, apex: &str, primaire: SocketAddr, cle: Option<TsigKey>) -> Self {
+        println!("Zone secondaire {} reprise de {}", apex, primaire);
+        self.secondaires.push(ZoneSecondaire { apex: apex.to_string(), primaire, cle });
+        self
+    }
+
+    /// Accepte les requêtes signées avec `cle` (TSIG), qui autorisent transferts et mises à jour
+    pub fn avec_cle_tsig(mut self, cle: TsigKey) -> Self {
+        self.cles_tsig.insert(cle);
+        self
+    }
+
+    /// Limite le débit des réponses UDP (RRL) et le nombre de requêtes par client
+    pub fn avec_limitation(mut self, config: RateLimitConfig) -> Self {
+        println!("Limitation de débit: {} réponses/s, {} erreurs/s, quota de {} requêtes/s par client, glissement 1/{}",
+            config.responses_per_second, config.errors_per_second, config.queries_per_second, config.slip);
+        self.limiteur = Some(Mutex::new(RateLimiter::new(config)));
+        self
+    }
+
+    /// Consigne chaque requête et sa réponse dans `journal`
+    pub fn avec_journal_requetes(mut self, journal: QueryLog) -> Self {
+        println!("Requêtes journalisées dans {}", journal.path().display());
+        self.journal_requetes = Some(Mutex::new(journal));
+        self
+    }
+
+    /// Compteurs de requêtes, de cache et de limitation
+    pub fn statistiques(&self) -> StatistiquesServeur {
+        StatistiquesServeur {
+            requetes: self.requetes.load(Ordering::Relaxed),
+            cache: self.cache.as_ref().map(|cache| cache.lock().unwrap().stats()),
+            limitation: self.limiteur.as_ref().map(|limiteur| limiteur.lock().unwrap().stats()),
+        }
+    }
+
+    /// Métriques du serveur au format texte de Prometheus
+    pub fn exposition_metriques(&self) -> String {
+        let cache = self.cache.as_ref().map(|cache| cache.lock().unwrap().stats());
+        self.metriques.lock().unwrap().render(cache.as_ref())
+    }
+
+    /// Sert les métriques en HTTP sur `ecoute` (`GET /metrics`), chaque connexion dans sa propre tâche
+    pub async fn servir_metriques(self: Arc<Self>, ecoute: TcpListener) {
+        if let Ok(adresse) = ecoute.local_addr() {
+            println!("Métriques disponibles sur http://{}/metrics", adresse);
+        }
+        loop {
+            match ecoute.accept().await {
+                Ok((flux, _)) => {
+                    let serveur = Arc::clone(&self);
+                    tokio::spawn(async move {
+                        if let Err(e) = tokio::time::timeout(DELAI_INACTIVITE_TCP, serveur.repondre_http(flux)).await {
+                            eprintln!("Requête HTTP abandonnée: {}", e);
+                        }
+                    });
+                }
+                Err(e) => {
+                    eprintln!("Erreur lors de l'acceptation HTTP: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Lit une requête HTTP et renvoie les métriques, ou une erreur pour tout autre chemin
+    async fn repondre_http(&self, mut flux: TcpStream) {
+        let mut requete = Vec::new();
+        let mut tampon = [0u8; 1024];
+        while !requete.windows(4).any(|fenetre| fenetre == b"\r\n\r\n") {
+            match flux.read(&mut tampon).await {
+                Ok(0) | Err(_) => return,
+                Ok(lus) => requete.extend_from_slice(&tampon[..lus]),
+            }
+            if requete.len() > TAILLE_MAX_REQUETE_HTTP {
+                return;
+            }
+        }
         
+        let texte = String::from_utf8_lossy(&requete);
+        let mut ligne = texte.lines().next().unwrap_or_default().split_whitespace();
+        let methode = ligne.next().unwrap_or_default();
+        let chemin = ligne.next().unwrap_or_default().split('?').next().unwrap_or_default();
+        let (statut, corps) = match (methode, chemin) {
+            ("GET", "/metrics") => ("200 OK", self.exposition_metriques()),
+            ("GET", _) => ("404 Not Found", "chemin inconnu, essayez /metrics\n".to_string()),
+            _ => ("405 Method Not Allowed", "seule la méthode GET est acceptée\n".to_string()),
+        };
+        let reponse = format!(
+            "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            statut, corps.len(), corps
+        );
+        let _ = flux.write_all(reponse.as_bytes()).await;
+        let _ = flux.shutdown().await;
+    }
+
+    /// Affiche périodiquement les statistiques du serveur
+    fn afficher_statistiques(self: &Arc<Self>) {
+        let serveur = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(INTERVALLE_STATISTIQUES).await;
+                let statistiques = serveur.statistiques();
+                print!("Statistiques: {} requêtes", statistiques.requetes);
+                if let Some(limitation) = statistiques.limitation {
+                    print!(", {} réponses envoyées, {} tronquées, {} ignorées (RRL), {} requêtes hors quota",
+                        limitation.allowed, limitation.slipped, limitation.dropped, limitation.over_quota);
+                }
+                println!();
+            }
+        });
+    }
+
+    /// Autorise les adresses données à modifier nos zones par mise à jour dynamique
+    pub fn avec_mises_a_jour_autorisees(mut self, adresses: Vec<IpAddr>) -> Self {
+        self.mises_a_jour_autorisees = adresses;
+        self
+    }
+
+    /// Enregistre les zones dans `chemin` après chaque mise à jour dynamique
+    pub fn avec_sauvegarde(self, chemin: PathBuf) -> Self {
+        *self.sauvegarde.lock().unwrap() = Some(chemin);
+        self
+    }
+
+    /// Adresse sur laquelle le serveur écoute réellement
+    pub fn adresse_locale(&self) -> std::io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Version courante de la table des enregistrements
+    fn table(&self) -> Arc<RecordTable> {
+        self.domaines.read().unwrap().clone()
+    }
+
+    /// Table servie dérivée de la table source : les PTR inverses sont générés si la synthèse est active
+    fn deriver(&self, source: &RecordTable) -> RecordTable {
+        let mut servie = source.clone();
+        if self.synthese_ptr {
+            servie.synthesize_ptr();
+        }
+        servie
+    }
+
+    /// Table servie à `client` : celle de sa vue s'il appartient au réseau de l'une d'elles,
+    /// la table commune sinon
+    fn table_pour(&self, client: IpAddr) -> Arc<RecordTable> {
+        let domaines = self.domaines.read().unwrap();
+        domaines
+            .view_for(client)
+            .and_then(|vue| self.vues.read().unwrap().get(&vue.name).cloned())
+            .unwrap_or_else(|| Arc::clone(&domaines))
+    }
+
+    /// Combine chaque vue de `table` aux enregistrements communs, avec les PTR inverses
+    /// de ses propres adresses si la synthèse est active
+    fn preparer_vues(table: &RecordTable, synthese_ptr: bool) -> HashMap<String, Arc<RecordTable>> {
+        table
+            .views()
+            .iter()
+            .map(|vue| {
+                let mut combinee = table.with_view(vue);
+                if synthese_ptr {
+                    combinee.synthesize_ptr();
+                }
+                (vue.name.clone(), Arc::new(combinee))
+            })
+            .collect()
+    }
+
+    /// Recharge le fichier de zone et remplace la table si elle est valide.
+    /// En cas d'erreur, l'ancienne table reste servie.
+    pub fn recharger_zone(&self, chemin: &Path) -> Result<ZoneDiff, ZoneError> {
+        let mut nouvelle = RecordTable::load(chemin)?;
+        nouvelle.validate()?;
+
+        self.modifier_table(|table| {
+            // Les zones secondaires ne proviennent pas du fichier : elles sont conservées
+            for zone in &self.secondaires {
+                nouvelle.replace_zone(&zone.apex, table.zone_records(&zone.apex));
+            }
+            *table = nouvelle;
+            Ok(())
+        })
+    }
+
+    /// Applique `modification` à une copie de la table source, puis met en service la table
+    /// qui en est dérivée si elle reste valide. Les changements de chaque zone servie sont
+    /// ajoutés au journal.
+    fn modifier_table<E: From<ZoneError>>(&self, modification: impl FnOnce(&mut RecordTable) -> Result<(), E>) -> Result<ZoneDiff, E> {
+        let mut source = self.source.write().unwrap();
+        let mut nouvelle = RecordTable::clone(&source);
+        modification(&mut nouvelle)?;
+        nouvelle.validate()?;
+
+        let servie = self.deriver(&nouvelle);
+        let mut domaines = self.domaines.write().unwrap();
+        self.journal.lock().unwrap().record(&domaines, &servie);
+        let diff = domaines.diff(&servie);
+        *self.vues.write().unwrap() = Self::preparer_vues(&nouvelle, self.synthese_ptr);
+        *domaines = Arc::new(servie);
+        *source = Arc::new(nouvelle);
+        Ok(diff)
+    }
+
+    /// Recharge la zone et journalise le résultat
+    fn recharger_et_journaliser(&self, chemin: &Path, raison: &str) {
+        match self.recharger_zone(chemin) {
+            Ok(diff) if diff.is_empty() => {
+                println!("Zone rechargée ({}) : aucun changement", raison);
+            }
+            Ok(diff) => {
+                println!("Zone rechargée ({}) : {} ajout(s), {} suppression(s)",
+                    raison, diff.added.len(), diff.removed.len());
+                for record in &diff.added {
+                    println!("   + {}", record);
+                }
+                for record in &diff.removed {
+                    println!("   - {}", record);
+                }
+            }
+            Err(e) => {
+                eprintln!("Rechargement refusé ({}), ancienne zone conservée: {}", raison, e);
+            }
+        }
+    }
+
+    /// Surveille le fichier de zone et le recharge lorsqu'il change ou à la réception de SIGHUP
+    pub fn surveiller_zone(self: &Arc<Self>, chemin: PathBuf) {
+        #[cfg(unix)]
+        {
+            let serveur = Arc::clone(self);
+            let chemin = chemin.clone();
+            tokio::spawn(async move {
+                use tokio::signal::unix::{signal, SignalKind};
+                let mut sighup = match signal(SignalKind::hangup()) {
+                    Ok(sighup) => sighup,
+                    Err(e) => {
+                        eprintln!("Impossible d'écouter SIGHUP: {}", e);
+                        return;
+                    }
+                };
+                while sighup.recv().await.is_some() {
+                    serveur.recharger_et_journaliser(&chemin, "SIGHUP");
+                }
+            });
+        }
+
+        let serveur = Arc::clone(self);
+        tokio::spawn(async move {
+            let version = |chemin: &Path| -> Option<(SystemTime, u64)> {
+                let meta = std::fs::metadata(chemin).ok()?;
+                Some((meta.modified().ok()?, meta.len()))
+            };
+            let mut derniere_version = version(&chemin);
+            let mut intervalle = tokio::time::interval(INTERVALLE_SURVEILLANCE);
+
+            loop {
+                intervalle.tick().await;
+                let version_courante = version(&chemin);
+                if version_courante.is_some() && version_courante != derniere_version {
+                    derniere_version = version_courante;
+                    serveur.recharger_et_journaliser(&chemin, "fichier modifié");
+                }
+            }
+        });
+    }
+
+    /// Transfère la zone `apex` depuis `primaire` : en IXFR si une version est déjà servie,
+    /// en AXFR sinon
+    pub async fn transferer_zone(&self, apex: &str, primaire: SocketAddr, cle: Option<&TsigKey>) -> Result<ZoneDiff, Box<dyn std::error::Error + Send + Sync>> {
+        let soa_local = self.table().soa(apex).cloned();
+        let qtype = if soa_local.is_some() { DNS_TYPE_IXFR } else { DNS_TYPE_AXFR };
+        let mut requete = DnsMessage::new_query(rand::random(), apex.to_string(), qtype)?;
+        requete.header.set_recursion_desired(false);
+        if let Some(soa) = soa_local {
+            // Le SOA de la section autorité indique la version détenue (RFC 1995)
+            requete.add_authority(soa);
+        }
+
+        let transfert = tokio::time::timeout(DELAI_TRANSFERT, Self::recevoir_transfert(&requete, primaire, cle)).await??;
+        Ok(self.modifier_table(|table| transfert.apply(table, apex).map(|_| ()))?)
+    }
+
+    /// Traite une mise à jour dynamique (RFC 2136) : les prérequis sont vérifiés et les
+    /// modifications appliquées sur une même version de la table, puis la zone est enregistrée
+    fn mettre_a_jour(&self, requete: &DnsMessage, client: IpAddr, cle: Option<&TsigKey>) -> DnsMessage {
+        let mut reponse = DnsMessage::new_response(requete);
+        
+        // Une requête signée par une clé connue vaut autorisation
+        if cle.is_none() && !self.mises_a_jour_autorisees.contains(&client) {
+            println!("Mise à jour dynamique refusée à {}", client);
+            reponse.header.set_rcode(Rcode::Refused);
+            return reponse;
+        }
+        let maj = match Update::from_message(requete) {
+            Ok(maj) => maj,
+            Err(rcode) => {
+                reponse.header.set_rcode(rcode);
+                return reponse;
+            }
+        };
+        
+        // Seules les zones dont nous sommes le primaire peuvent être modifiées
+        let secondaire = self.secondaires.iter().any(|zone| zone.apex.eq_ignore_ascii_case(maj.zone.trim_end_matches('.')));
+        // Les zones inverses générées ne figurent pas dans la table source
+        if secondaire || self.source.read().unwrap().soa(&maj.zone).is_none() {
+            println!("Mise à jour refusée: {} n'est pas une zone primaire", maj.zone);
+            reponse.header.set_rcode(Rcode::NotAuth);
+            return reponse;
+        }
+        if let Err(rcode) = maj.prescan() {
+            reponse.header.set_rcode(rcode);
+            return reponse;
+        }
+        
+        let resultat = self.modifier_table(|table| {
+            maj.check_prerequisites(table).map_err(EchecMiseAJour::Rcode)?;
+            maj.apply(table);
+            Ok(())
+        });
+        match resultat {
+            Ok(diff) if diff.is_empty() => println!("Mise à jour de {} par {} : aucun changement", maj.zone, client),
+            Ok(diff) => {
+                println!("Mise à jour de {} par {} : {} ajout(s), {} suppression(s)",
+                    maj.zone, client, diff.added.len(), diff.removed.len());
+                self.sauvegarder();
+            }
+            Err(EchecMiseAJour::Rcode(rcode)) => {
+                println!("Prérequis de mise à jour non satisfaits: {}", rcode);
+                reponse.header.set_rcode(rcode);
+            }
+            Err(EchecMiseAJour::Zone(e)) => {
+                eprintln!("Mise à jour refusée, la zone serait invalide: {}", e);
+                reponse.header.set_rcode(Rcode::Refused);
+            }
+        }
+        reponse
+    }
+
+    /// Écrit la table source dans le fichier de sauvegarde, hors zones secondaires et PTR générés.
+    /// Le fichier est remplacé d'un bloc pour ne jamais être lu à moitié écrit.
+    fn sauvegarder(&self) {
+        let sauvegarde = self.sauvegarde.lock().unwrap();
+        let Some(chemin) = sauvegarde.as_ref() else {
+            return;
+        };
+        
+        let mut table = RecordTable::clone(&self.source.read().unwrap());
+        for zone in &self.secondaires {
+            table.replace_zone(&zone.apex, Vec::new());
+        }
+        let temporaire = chemin.with_extension("tmp");
+        let resultat = std::fs::write(&temporaire, table.to_string())
+            .and_then(|_| std::fs::rename(&temporaire, chemin));
+        if let Err(e) = resultat {
+            eprintln!("Enregistrement de {} impossible: {}", chemin.display(), e);
+        }
+    }
+
+    /// Envoie une requête de transfert sur TCP et lit les messages jusqu'au SOA final.
+    /// Avec une clé, la requête est signée et chaque message reçu doit l'être aussi.
+    async fn recevoir_transfert(requete: &DnsMessage, primaire: SocketAddr, cle: Option<&TsigKey>) -> Result<Transfer, Box<dyn std::error::Error + Send + Sync>> {
+        let qtype = requete.questions[0].qtype;
+        let (octets, mut signature) = match cle {
+            Some(cle) => {
+                let (octets, signature) = sign_request(&requete.to_bytes(), cle, unix_time())?;
+                (octets, Some(signature))
+            }
+            None => (requete.to_bytes(), None),
+        };
+        let mut flux = TcpStream::connect(primaire).await?;
+        write_tcp_message(&mut flux, &octets).await?;
+
+        let mut records = Vec::new();
         loop {
+            let donnees = read_tcp_message(&mut flux).await?;
+            if let Some(signature) = signature.as_mut() {
+                signature.verify_response(&donnees, unix_time())?;
+            }
+            let reponse = DnsMessage::from_bytes(&donnees)?;
+            if reponse.header.id != requete.header.id {
+                return Err("identifiant de réponse de transfert inattendu".into());
+            }
+            if reponse.rcode() != Rcode::NoError {
+                return Err(format!("transfert refusé par {}: {}", primaire, reponse.rcode()).into());
+            }
+            records.extend(reponse.answers);
+            if let Some(transfert) = Transfer::parse(&records, qtype)? {
+                return Ok(transfert);
+            }
+        }
+    }
+
+    /// Reprend une zone secondaire maintenant, puis à chaque intervalle de rafraîchissement
+    /// de son SOA (ou de nouvel essai après un échec)
+    fn suivre_primaire(self: &Arc<Self>, zone: ZoneSecondaire) {
+        let serveur = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                let reussi = match serveur.transferer_zone(&zone.apex, zone.primaire, zone.cle.as_ref()).await {
+                    Ok(diff) => {
+                        println!("Zone {} à jour depuis {} : {} ajout(s), {} suppression(s)",
+                            zone.apex, zone.primaire, diff.added.len(), diff.removed.len());
+                        true
+                    }
+                    Err(e) => {
+                        eprintln!("Transfert de {} depuis {} impossible: {}", zone.apex, zone.primaire, e);
+                        false
+                    }
+                };
+
+                let delai = match serveur.table().soa(&zone.apex).map(|soa| &soa.rdata) {
+                    Some(RData::Soa { refresh, retry, .. }) => {
+                        Duration::from_secs(u64::from(if reussi { *refresh } else { *retry }).max(1))
+                    }
+                    _ => DELAI_RELANCE_SECONDAIRE,
+                };
+                tokio::time::sleep(delai).await;
+            }
+        });
+    }
+
+    /// Démarre l'écoute des requêtes DNS. Chaque requête est traitée dans sa propre tâche,
+    /// dans la limite du nombre de traitements simultanés autorisés.
+    pub async fn demarrer(self: Arc<Self>) -> Result<(), Box<dyn std::error::Error>> {
+        // Assez grand pour les requêtes EDNS(0)
+        let mut buffer = vec![0u8; EDNS_UDP_PAYLOAD as usize];
+        
+        // Les connexions TCP sont acceptées en parallèle de l'écoute UDP
+        tokio::spawn(Arc::clone(&self).ecouter_tcp());
+        
+        // Les zones secondaires sont transférées en tâche de fond
+        for zone in self.secondaires.clone() {
+            self.suivre_primaire(zone);
+        }
+        
+        if self.limiteur.is_some() {
+            self.afficher_statistiques();
+        }
+        
+        loop {
+            // Attend qu'un traitement se libère avant de lire le datagramme suivant
+            let permis = Arc::clone(&self.concurrence).acquire_owned().await?;
+            
             // Attend une requête
             match self.socket.recv_from(&mut buffer).await {
                 Ok((taille, adresse_client)) => {
                     println!("Requête reçue de {} ({} bytes)", adresse_client, taille);
                     
                     // Traite la requête de manière asynchrone
-                    if let Err(e) = self.traiter_requete(&buffer[..taille], adresse_client).await {
-                        eprintln!("Erreur lors du traitement de la requête: {}", e);
-                    }
+                    let serveur = Arc::clone(&self);
+                    let donnees = buffer[..taille].to_vec();
+                    tokio::spawn(async move {
+                        if let Err(e) = serveur.traiter_requete(&donnees, adresse_client).await {
+                            eprintln!("Erreur lors du traitement de la requête: {}", e);
+                        }
+                        drop(permis);
+                    });
                 }
                 Err(e) => {
                     eprintln!("Erreur lors de la réception: {}", e);
@@ -56,57 +845,224 @@ impl ServeurDns {
         }
     }
 
+    /// Accepte les connexions TCP et traite chacune dans sa propre tâche
+    async fn ecouter_tcp(self: Arc<Self>) {
+        loop {
+            match self.ecoute_tcp.accept().await {
+                Ok((flux, adresse_client)) => {
+                    println!("Connexion TCP de {}", adresse_client);
+                    tokio::spawn(Arc::clone(&self).traiter_connexion_tcp(flux, adresse_client));
+                }
+                Err(e) => {
+                    eprintln!("Erreur lors de l'acceptation TCP: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Traite les requêtes successives d'une connexion TCP (messages préfixés par leur longueur)
+    async fn traiter_connexion_tcp(self: Arc<Self>, mut flux: TcpStream, adresse_client: SocketAddr) {
+        loop {
+            let donnees = match tokio::time::timeout(DELAI_INACTIVITE_TCP, read_tcp_message(&mut flux)).await {
+                Ok(Ok(donnees)) => donnees,
+                // Fin de connexion, erreur de lecture ou inactivité
+                _ => break,
+            };
+            println!("Requête TCP reçue de {} ({} bytes)", adresse_client, donnees.len());
+            
+            let Ok(_permis) = self.concurrence.acquire().await else {
+                break;
+            };
+            let reponses = self.traiter_message(&donnees, adresse_client.ip(), true).await;
+            if reponses.is_empty() {
+                break;
+            }
+            
+            for reponse in reponses {
+                if let Err(e) = write_tcp_message(&mut flux, &reponse).await {
+                    eprintln!("Erreur envoi réponse TCP: {}", e);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Répond à une demande de transfert de zone (AXFR ou IXFR) par une suite de messages
+    fn repondre_transfert(&self, requete: &DnsMessage, client: IpAddr, cle: Option<&TsigKey>) -> Vec<DnsMessage> {
+        let erreur = |rcode| {
+            let mut reponse = DnsMessage::new_response(requete);
+            reponse.header.set_rcode(rcode);
+            vec![reponse]
+        };
+        
+        if cle.is_none() && !self.transferts_autorises.contains(&client) {
+            println!("Transfert de zone refusé à {}", client);
+            return erreur(Rcode::Refused);
+        }
+        if requete.questions.len() != 1 {
+            return erreur(Rcode::FormErr);
+        }
+        
+        let question = &requete.questions[0];
+        let domaines = self.table();
+        let Some(soa) = domaines.soa(&question.qname).cloned() else {
+            println!("Transfert demandé pour {}, qui n'est pas un sommet de zone", question.qname);
+            return erreur(Rcode::NotAuth);
+        };
+        
+        let records = if question.qtype == DNS_TYPE_IXFR {
+            let serial_client = requete.authorities.iter().find_map(|record| match record.rdata {
+                RData::Soa { serial, .. } => Some(serial),
+                _ => None,
+            });
+            let Some(serial_client) = serial_client else {
+                return erreur(Rcode::FormErr);
+            };
+            let serial_zone = match soa.rdata {
+                RData::Soa { serial, .. } => serial,
+                _ => 0,
+            };
+            
+            if !serial_newer(serial_zone, serial_client) {
+                // Le secondaire est à jour : le SOA seul suffit
+                vec![soa]
+            } else {
+                let increment = self.journal.lock().unwrap().ixfr(&domaines, &question.qname, serial_client);
+                // Sans historique depuis la version du secondaire, la zone est envoyée en entier
+                increment.unwrap_or_else(|| domaines.axfr(&question.qname).unwrap_or_default())
+            }
+        } else {
+            domaines.axfr(&question.qname).unwrap_or_default()
+        };
+        
+        println!("Transfert {} de {} vers {} ({} enregistrements)",
+            type_to_string(question.qtype), question.qname, client, records.len());
+        transfer_messages(requete, records)
+    }
+
     /// Traite une requête DNS et envoie la réponse
     async fn traiter_requete(&self, donnees: &[u8], adresse_client: SocketAddr) -> Result<(), Box<dyn std::error::Error>> {
-        // Parse la requête DNS
-        let requete = match DnsMessage::from_bytes(donnees) {
-            Ok(msg) => msg,
-            Err(e) => {
-                eprintln!("Erreur parsing requête DNS: {}", e);
-                return Ok(());
+        for reponse_bytes in self.traiter_message(donnees, adresse_client.ip(), false).await {
+            self.envoyer_udp(&reponse_bytes, adresse_client).await;
+        }
+        Ok(())
+    }
+
+    /// Construit les réponses encodées à un message reçu : plusieurs pour un transfert de zone,
+    /// aucune si le message doit être ignoré. Une requête signée (TSIG) est vérifiée avant tout
+    /// traitement, et ses réponses sont signées avec la même clé.
+    async fn traiter_message(&self, donnees: &[u8], client: IpAddr, tcp: bool) -> Vec<Vec<u8>> {
+        let debut = Instant::now();
+        self.requetes.fetch_add(1, Ordering::Relaxed);
+        
+        // Au-delà de son quota, un client UDP n'obtient plus de réponse. En TCP, l'adresse
+        // source est vérifiée par la connexion et ne peut pas servir à une amplification.
+        let limiteur = self.limiteur.as_ref().filter(|_| !tcp);
+        if limiteur.is_some_and(|limiteur| !limiteur.lock().unwrap().allow_query(client)) {
+            println!("Requête de {} ignorée (quota dépassé)", client);
+            self.metriques.lock().unwrap().record_drop("quota");
+            return Vec::new();
+        }
+        
+        let requete = match Self::decoder_requete(donnees) {
+            Ok(requete) => requete,
+            Err(Some(formerr)) => {
+                self.metriques.lock().unwrap().record_response(Rcode::FormErr, debut.elapsed());
+                return vec![formerr.to_bytes()];
+            }
+            Err(None) => {
+                self.metriques.lock().unwrap().record_drop("invalide");
+                return Vec::new();
             }
         };
-
         println!("ID requête: {}", requete.header.id);
+        if let Some(question) = requete.questions.first() {
+            self.metriques.lock().unwrap().record_query(question.qtype);
+        }
         
-        // Crée la réponse
-        let mut reponse = DnsMessage::new_response(&requete);
+        let mut signature = match verify_request(donnees, &self.cles_tsig, unix_time()) {
+            Ok(signature) => signature,
+            Err(echec) => {
+                println!("Requête de {} refusée, signature TSIG invalide: {}", client, echec.error);
+                self.metriques.lock().unwrap().record_response(echec.error.rcode(), debut.elapsed());
+                return vec![echec.encode_response(&mut DnsMessage::new_response(&requete), unix_time())];
+            }
+        };
+        let cle = signature.as_ref().map(|signature| signature.key().clone());
         
-        // Traite chaque question
-        for question in &requete.questions {
-            println!("Question: {} (type: {})", question.qname, question.qtype);
-            
-            // Vérifie si on connaît ce domaine et si c'est une requête de type A
-            if question.qtype == DNS_TYPE_A {
-                if let Some(ip) = self.domaines.get(&question.qname) {
-                    // Ajoute la réponse
-                    let answer = DnsAnswer::new_a_record(
-                        question.qname.clone(),
-                        *ip,
-                        300 // TTL de 5 minutes
-                    );
-                    
-                    reponse.answers.push(answer);
-                    reponse.header.ancount += 1;
-                    
-                    println!("Réponse: {} -> {}.{}.{}.{}", 
-                        question.qname, ip[0], ip[1], ip[2], ip[3]);
-                } else {
-                    println!("Domaine inconnu: {}", question.qname);
-                    // Marque comme erreur (NXDOMAIN)
-                    reponse.header.flags |= 0x0003; // RCODE = 3 (NXDOMAIN)
+        let reponses = if tcp && est_transfert(&requete) {
+            self.repondre_transfert(&requete, client, cle.as_ref())
+        } else if requete.header.opcode() == Opcode::Update {
+            vec![self.mettre_a_jour(&requete, client, cle.as_ref())]
+        } else {
+            vec![self.repondre(&requete, client).await]
+        };
+        self.journaliser(client, tcp, &requete, &reponses, debut.elapsed());
+        let reponses: Vec<DnsMessage> = match limiteur {
+            Some(limiteur) => reponses
+                .into_iter()
+                .filter_map(|reponse| Self::limiter_reponse(limiteur, &requete, reponse, client))
+                .collect(),
+            None => reponses,
+        };
+        match reponses.first() {
+            Some(reponse) => self.metriques.lock().unwrap().record_response(reponse.rcode(), debut.elapsed()),
+            None => self.metriques.lock().unwrap().record_drop("rrl"),
+        }
+        
+        // En UDP, la réponse est tronquée à la taille acceptée par le client (512 octets,
+        // ou la taille annoncée en EDNS), en gardant la place de la signature
+        let taille_udp = requete.max_udp_response_size(EDNS_UDP_PAYLOAD)
+            - signature.as_ref().map_or(0, TsigContext::overhead);
+        reponses
+            .into_iter()
+            .map(|reponse| {
+                // Pas de limite de taille en TCP : la réponse n'est jamais tronquée
+                let octets = if tcp { reponse.to_bytes() } else { reponse.to_bytes_truncated(taille_udp) };
+                if DnsHeader::from_bytes(&octets).is_ok_and(|entete| entete.truncated()) {
+                    println!("Réponse tronquée (bit TC), le client doit réessayer en TCP");
                 }
-            } else {
-                println!("Type de requête non supporté: {}", question.qtype);
-                // Marque comme non implémenté
-                reponse.header.flags |= 0x0004; // RCODE = 4 (Not Implemented)
+                match signature.as_mut() {
+                    Some(signature) => signature.sign_response(&octets, unix_time()),
+                    None => octets,
+                }
+            })
+            .collect()
+    }
+
+    /// Ajoute l'échange au journal des requêtes, s'il est actif
+    fn journaliser(&self, client: IpAddr, tcp: bool, requete: &DnsMessage, reponses: &[DnsMessage], latence: Duration) {
+        let Some(journal) = &self.journal_requetes else {
+            return;
+        };
+        let entree = QueryLogEntry::new(client, if tcp { "tcp" } else { "udp" }, requete, reponses, latence);
+        if let Err(e) = journal.lock().unwrap().write(&entree) {
+            eprintln!("Écriture du journal des requêtes impossible: {}", e);
+        }
+    }
+
+    /// Applique la limitation de débit (RRL) à une réponse UDP : elle est envoyée, remplacée
+    /// par une réponse vide tronquée qui renvoie un vrai client vers TCP, ou ignorée
+    fn limiter_reponse(limiteur: &Mutex<RateLimiter>, requete: &DnsMessage, reponse: DnsMessage, client: IpAddr) -> Option<DnsMessage> {
+        match limiteur.lock().unwrap().check_response(client, &reponse) {
+            RateLimitDecision::Send => Some(reponse),
+            RateLimitDecision::Slip => {
+                println!("Réponse à {} limitée (RRL), remplacée par une réponse tronquée", client);
+                let mut tronquee = DnsMessage::new_response(requete);
+                tronquee.set_rcode(reponse.rcode());
+                tronquee.header.set_truncated(true);
+                Some(tronquee)
+            }
+            RateLimitDecision::Drop => {
+                println!("Réponse à {} limitée (RRL), ignorée", client);
+                None
             }
         }
+    }
 
-        // Sérialise et envoie la réponse
-        let reponse_bytes = reponse.to_bytes();
-        
-        match self.socket.send_to(&reponse_bytes, adresse_client).await {
+    /// Envoie une réponse UDP au client
+    async fn envoyer_udp(&self, reponse_bytes: &[u8], adresse_client: SocketAddr) {
+        match self.socket.send_to(reponse_bytes, adresse_client).await {
             Ok(bytes_envoyes) => {
                 println!("Réponse envoyée à {} ({} bytes)", adresse_client, bytes_envoyes);
             }
@@ -114,20 +1070,1068 @@ impl ServeurDns {
                 eprintln!("Erreur envoi réponse: {}", e);
             }
         }
+    }
 
-        Ok(())
+    /// Décode une requête reçue. En cas d'échec, retourne la réponse FORMERR à renvoyer,
+    /// ou `None` si le message doit être ignoré (en-tête illisible, ou message qui est une réponse)
+    fn decoder_requete(donnees: &[u8]) -> Result<DnsMessage, Option<DnsMessage>> {
+        let entete = match DnsHeader::from_bytes(donnees) {
+            Ok(entete) => entete,
+            Err(e) => {
+                eprintln!("Message ignoré: {}", e);
+                return Err(None);
+            }
+        };
+        if entete.is_response() {
+            eprintln!("Message ignoré: il s'agit d'une réponse");
+            return Err(None);
+        }
+
+        DnsMessage::from_bytes(donnees).map_err(|e| {
+            eprintln!("Requête mal formée ({}), réponse FORMERR", e);
+            // Seul l'en-tête est fiable : la réponse ne reprend pas la question
+            let requete = DnsMessage {
+                header: DnsHeader { qdcount: 0, ..entete },
+                questions: Vec::new(),
+                answers: Vec::new(),
+                authorities: Vec::new(),
+                additionals: Vec::new(),
+            };
+            let mut reponse = DnsMessage::new_response(&requete);
+            reponse.header.set_rcode(Rcode::FormErr);
+            Some(reponse)
+        })
+    }
+
+    /// Construit la réponse à une requête : depuis la table locale (celle de la vue du client),
+    /// ou via le résolveur amont pour les noms inconnus lorsque le mode transfert est actif
+    async fn repondre(&self, requete: &DnsMessage, client: IpAddr) -> DnsMessage {
+        let edns_requete = requete.edns();
+        
+        // Seule la version 0 d'EDNS est supportée
+        if edns_requete.as_ref().is_some_and(|edns| edns.version > 0) {
+            let mut reponse = DnsMessage::new_response(requete);
+            reponse.set_edns(Edns::new(EDNS_UDP_PAYLOAD));
+            reponse.set_rcode(Rcode::BadVers);
+            return reponse;
+        }
+        
+        let reponse = self.repondre_localement(requete, client);
+        
+        // Seuls les noms hors de nos zones sont transférés : nos réponses négatives font autorité
+        let hors_zone = reponse.header.rcode() == Rcode::Refused;
+        let mut reponse = match self.amont {
+            Some(amont) if hors_zone && requete.header.recursion_desired() => self.transmettre(requete, amont).await,
+            _ => reponse,
+        };
+        
+        // Un OPT n'est renvoyé que si le client en a envoyé un, en conservant le RCODE étendu
+        let rcode = reponse.rcode();
+        match edns_requete {
+            Some(_) => reponse.set_edns(Edns::new(EDNS_UDP_PAYLOAD)),
+            None => reponse.remove_edns(),
+        }
+        reponse.set_rcode(rcode);
+        reponse
+    }
+
+    /// Construit la réponse à partir de la table des enregistrements vue par `client`
+    fn repondre_localement(&self, requete: &DnsMessage, client: IpAddr) -> DnsMessage {
+        // Crée la réponse à partir d'une version figée de la table
+        let mut reponse = DnsMessage::new_response(requete);
+        let domaines = self.table_pour(client);
+        
+        // Le serveur n'offre la récursion qu'en mode transfert
+        reponse.header.set_recursion_available(self.amont.is_some());
+        
+        // Seules les requêtes standard sont prises en charge
+        if requete.header.opcode() != Opcode::Query {
+            println!("Opcode non supporté: {}", requete.header.opcode());
+            reponse.header.set_rcode(Rcode::NotImp);
+            return reponse;
+        }
+        
+        // Une requête porte exactement une question (RFC 9619)
+        if requete.questions.len() != 1 {
+            println!("Requête avec {} questions refusée", requete.questions.len());
+            reponse.header.set_rcode(Rcode::FormErr);
+            return reponse;
+        }
+        
+        let question = &requete.questions[0];
+        println!("Question: {} (type: {})", question.qname, type_to_string(question.qtype));
+        
+        // Le serveur ne répond que pour les noms de ses zones, avec autorité
+        if domaines.find_zone(&question.qname).is_none() {
+            println!("{} n'appartient à aucune zone servie", question.qname);
+            reponse.header.set_rcode(Rcode::Refused);
+            return reponse;
+        }
+        reponse.header.set_authoritative(true);
+        
+        // Les transferts de zone passent par TCP : le SOA seul, avec le bit TC, invite à réessayer
+        if est_transfert(requete) {
+            if let Some(soa) = domaines.soa(&question.qname) {
+                reponse.add_answer(soa.clone());
+            }
+            reponse.header.set_truncated(true);
+            return reponse;
+        }
+        
+        // Résout la question en suivant les CNAME et les jokers de la table
+        let resolution = domaines.resolve(&question.qname, question.qtype);
+        for record in resolution.answers {
+            println!("Réponse: {}", record);
+            reponse.add_answer(record);
+        }
+        
+        let negative = match resolution.status {
+            LookupStatus::Found => false,
+            LookupStatus::NoData => {
+                println!("Aucun enregistrement {} pour {}", type_to_string(question.qtype), resolution.final_name);
+                true
+            }
+            LookupStatus::NxDomain => {
+                println!("Domaine inconnu: {}", resolution.final_name);
+                // Un CNAME vers un nom hors de nos zones n'est pas une absence qui fait autorité
+                let dans_nos_zones = domaines.find_zone(&resolution.final_name).is_some();
+                if dans_nos_zones {
+                    reponse.header.set_rcode(Rcode::NxDomain);
+                }
+                dans_nos_zones
+            }
+            LookupStatus::CnameLoop => {
+                eprintln!("Boucle de CNAME à partir de {}", question.qname);
+                reponse.header.set_rcode(Rcode::ServFail);
+                false
+            }
+        };
+        
+        // Réponse négative (NXDOMAIN ou NODATA) : le SOA de la zone indique la durée
+        // pendant laquelle l'absence peut être mise en cache (RFC 2308)
+        if negative {
+            if let Some(soa) = domaines.find_zone(&resolution.final_name) {
+                let ttl = match soa.rdata {
+                    RData::Soa { minimum, .. } => soa.ttl.min(minimum),
+                    _ => soa.ttl,
+                };
+                reponse.add_authority(DnsAnswer { ttl, ..soa.clone() });
+            }
+        }
+
+        reponse
+    }
+
+    /// Transfère la requête au résolveur amont et relaie sa réponse au client, en passant
+    /// par le cache si possible. Répond SERVFAIL si l'amont ne répond pas à temps.
+    async fn transmettre(&self, requete: &DnsMessage, amont: ResolveurAmont) -> DnsMessage {
+        if let (Some(cache), Some(question)) = (&self.cache, requete.questions.first()) {
+            let mut cache = cache.lock().unwrap();
+            if let Some(en_cache) = cache.get(&question.qname, question.qtype, question.qclass) {
+                let stats = cache.stats();
+                println!("Réponse servie depuis le cache (succès: {}, échecs: {})", stats.hits, stats.misses);
+                let mut reponse = en_cache.to_response(requete);
+                reponse.header.set_recursion_available(true);
+                return reponse;
+            }
+        }
+        
+        match Self::interroger_amont(requete, amont).await {
+            Ok(mut reponse) => {
+                println!("Réponse relayée depuis {} ({} réponse(s))", amont.adresse, reponse.answers.len());
+                if let (Some(cache), Some(question)) = (&self.cache, requete.questions.first()) {
+                    cache.lock().unwrap().insert(question, &reponse);
+                }
+                // Rétablit l'identifiant du client et annonce la récursion
+                reponse.header.id = requete.header.id;
+                reponse.header.set_recursion_available(true);
+                reponse
+            }
+            Err(e) => {
+                eprintln!("Échec du transfert vers {}: {}", amont.adresse, e);
+                let mut reponse = DnsMessage::new_response(requete);
+                reponse.header.set_recursion_available(true);
+                reponse.header.set_rcode(Rcode::ServFail);
+                reponse
+            }
+        }
+    }
+
+    /// Envoie la requête à l'amont avec un nouvel identifiant et attend la réponse correspondante
+    async fn interroger_amont(requete: &DnsMessage, amont: ResolveurAmont) -> Result<DnsMessage, Box<dyn std::error::Error + Send + Sync>> {
+        let adresse_locale = if amont.adresse.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(adresse_locale).await?;
+        
+        // Un identifiant propre à l'amont évite de dépendre de celui choisi par le client
+        let id_amont = rand::random::<u16>();
+        let mut transmise = requete.clone();
+        transmise.header.id = id_amont;
+        socket.send_to(&transmise.to_bytes(), amont.adresse).await?;
+        
+        let limite = Instant::now() + amont.delai;
+        let mut buffer = vec![0u8; EDNS_UDP_PAYLOAD as usize];
+        loop {
+            let (taille, source) = tokio::time::timeout_at(limite, socket.recv_from(&mut buffer)).await??;
+            
+            // Ignore les datagrammes qui ne correspondent pas à notre requête (source,
+            // identifiant ou question), pour ne jamais relayer une réponse sur un autre nom
+            if source != amont.adresse {
+                continue;
+            }
+            match DnsMessage::from_bytes(&buffer[..taille]) {
+                Ok(reponse) if reponse.header.id == id_amont && reponse.header.is_response() && reponse.same_questions(&transmise) => {
+                    // Réponse tronquée : la requête est reprise en TCP
+                    if reponse.header.truncated() {
+                        return tokio::time::timeout_at(limite, Self::interroger_amont_tcp(&transmise, amont)).await?;
+                    }
+                    return Ok(reponse);
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    /// Envoie une requête à l'amont sur TCP
+    async fn interroger_amont_tcp(requete: &DnsMessage, amont: ResolveurAmont) -> Result<DnsMessage, Box<dyn std::error::Error + Send + Sync>> {
+        let mut flux = TcpStream::connect(amont.adresse).await?;
+        write_tcp_message(&mut flux, &requete.to_bytes()).await?;
+        let reponse = DnsMessage::from_bytes(&read_tcp_message(&mut flux).await?)?;
+        if reponse.header.id != requete.header.id {
+            return Err("identifiant de réponse TCP inattendu".into());
+        }
+        if !reponse.same_questions(requete) {
+            return Err("question de la réponse TCP différente de la requête".into());
+        }
+        Ok(reponse)
     }
 }
 
+/// Indique si la requête demande un transfert de zone
+fn est_transfert(requete: &DnsMessage) -> bool {
+    requete.questions.first().is_some_and(|question| question.qtype == DNS_TYPE_AXFR || question.qtype == DNS_TYPE_IXFR)
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Démarrage du serveur DNS simple...");
     
+    let config = match ConfigServeur::depuis_arguments(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Erreur: {}", e);
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+    
+    // Charge la zone en signalant la ligne fautive en cas d'erreur
+    let domaines = match config.charger_zone() {
+        Ok(domaines) => domaines,
+        Err(e) => {
+            eprintln!("Fichier de zone invalide: {}", e);
+            std::process::exit(1);
+        }
+    };
+    
     // Crée et démarre le serveur sur un port non privilégié
-    let serveur = ServeurDns::new("127.0.0.1:8053").await?;
+    let mut serveur = ServeurDns::new(&config.adresse, domaines)
+        .await?
+        .avec_limite_concurrence(config.limite_concurrence);
+    if let Some(amont) = config.amont {
+        serveur = serveur
+            .avec_amont(amont, config.delai_amont)
+            .avec_cache(config.capacite_cache);
+    }
+    if config.synthese_ptr {
+        serveur = serveur.avec_synthese_ptr();
+    }
+    serveur = serveur.avec_transferts_autorises(config.transferts_autorises.clone());
+    for cle in &config.cles_tsig {
+        serveur = serveur.avec_cle_tsig(cle.clone());
+    }
+    for (zone, primaire, nom_cle) in &config.secondaires {
+        let cle = match nom_cle {
+            Some(nom) => match config.cles_tsig.iter().find(|cle| cle.name.eq_ignore_ascii_case(nom)) {
+                Some(cle) => Some(cle.clone()),
+                None => {
+                    eprintln!("Erreur: clé TSIG {} inconnue (à déclarer avec --tsig)", nom);
+                    std::process::exit(2);
+                }
+            },
+            None => None,
+        };
+        serveur = serveur.avec_zone_secondaire(zone, *primaire, cle);
+    }
+    serveur = serveur.avec_mises_a_jour_autorisees(config.mises_a_jour_autorisees.clone());
+    if let Some(limitation) = config.limitation {
+        serveur = serveur.avec_limitation(limitation);
+    }
+    if let Some(chemin) = &config.journal_requetes {
+        match QueryLog::open(chemin, config.taille_journal, config.journaux_conserves) {
+            Ok(journal) => serveur = serveur.avec_journal_requetes(journal),
+            Err(e) => {
+                eprintln!("Erreur: ouverture du journal {} impossible: {}", chemin.display(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(chemin) = &config.fichier_sauvegarde {
+        serveur = serveur.avec_sauvegarde(chemin.clone());
+    }
+    let serveur = Arc::new(serveur);
+    
+    // Point d'accès HTTP des métriques, s'il est demandé
+    if let Some(adresse) = config.metriques {
+        let ecoute = TcpListener::bind(adresse).await?;
+        tokio::spawn(Arc::clone(&serveur).servir_metriques(ecoute));
+    }
+    
+    // Recharge la zone à chaud si elle provient d'un fichier
+    if let Some(chemin) = &config.fichier_zone {
+        serveur.surveiller_zone(chemin.clone());
+    }
     
     // Démarre l'écoute
     serveur.demarrer().await?;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tp7_dns::{TsigError, DNS_TYPE_A};
+
+    /// Adresse de client utilisée pour les appels directs à `repondre`
+    const CLIENT: IpAddr = IpAddr::V4(std::net::Ipv4Addr::LOCALHOST);
+
+    /// Fichier temporaire propre à un test
+    fn fichier_temporaire(nom: &str, contenu: &str) -> PathBuf {
+        let chemin = std::env::temp_dir().join(format!("tp7_{}_{}.zone", nom, std::process::id()));
+        std::fs::write(&chemin, contenu).unwrap();
+        chemin
+    }
+
+    /// Zone de test faisant autorité pour `origine` (SOA et NS), suivie des enregistrements donnés
+    fn zone(origine: &str, enregistrements: &str) -> String {
+        format!("$ORIGIN {}.\n@ SOA ns admin 1 3600 600 86400 60\n@ NS ns\n{}", origine, enregistrements)
+    }
+
+    /// Crée un serveur de test sur un port libre servant la zone donnée
+    async fn serveur_test(zone: &str) -> ServeurDns {
+        let table = RecordTable::parse(zone, None).unwrap();
+        ServeurDns::new("127.0.0.1:0", table).await.unwrap()
+    }
+
+    /// Démarre le serveur en tâche de fond et retourne son adresse
+    fn lancer(serveur: ServeurDns) -> SocketAddr {
+        lancer_partage(Arc::new(serveur))
+    }
+
+    /// Démarre en tâche de fond un serveur que le test continue de manipuler
+    fn lancer_partage(serveur: Arc<ServeurDns>) -> SocketAddr {
+        let adresse = serveur.adresse_locale().unwrap();
+        tokio::spawn(async move {
+            let _ = serveur.demarrer().await;
+        });
+        adresse
+    }
+
+    /// Envoie une requête UDP et attend la réponse
+    async fn interroger(adresse: SocketAddr, id: u16, nom: &str, qtype: u16) -> DnsMessage {
+        echanger(adresse, &DnsMessage::new_query(id, nom.to_string(), qtype).unwrap()).await
+    }
+
+    /// Envoie un message UDP quelconque et attend la réponse
+    async fn echanger(adresse: SocketAddr, requete: &DnsMessage) -> DnsMessage {
+        DnsMessage::from_bytes(&echanger_octets(adresse, &requete.to_bytes()).await).unwrap()
+    }
+
+    /// Envoie un message UDP déjà encodé (signé par exemple) et retourne la réponse brute
+    async fn echanger_octets(adresse: SocketAddr, requete: &[u8]) -> Vec<u8> {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        socket.send_to(requete, adresse).await.unwrap();
+
+        let mut buffer = vec![0u8; 512];
+        let (taille, _) = tokio::time::timeout(Duration::from_secs(5), socket.recv_from(&mut buffer))
+            .await
+            .unwrap()
+            .unwrap();
+        buffer.truncate(taille);
+        buffer
+    }
+
+    #[tokio::test]
+    async fn test_requetes_simultanees() {
+        let adresse = lancer(serveur_test(&zone("a.com", "a.com. A 1.1.1.1\n")).await.avec_limite_concurrence(4));
+
+        // Plusieurs clients interrogent le serveur en même temps
+        let mut taches = Vec::new();
+        for id in 0..16u16 {
+            taches.push(tokio::spawn(interroger(adresse, id, "a.com", DNS_TYPE_A)));
+        }
+
+        for (id, tache) in taches.into_iter().enumerate() {
+            let reponse = tache.await.unwrap();
+            assert_eq!(reponse.header.id, id as u16);
+            assert_eq!(reponse.answers.len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transfert_vers_amont() {
+        let amont = lancer(serveur_test(&zone("com", "b.com. A 2.2.2.2\n")).await);
+        let adresse = lancer(serveur_test(&zone("a.com", "a.com. A 1.1.1.1\n")).await.avec_amont(amont, Duration::from_secs(2)));
+
+        // Nom local : réponse directe, récursion annoncée
+        let reponse = interroger(adresse, 10, "a.com", DNS_TYPE_A).await;
+        assert_eq!(reponse.answers.len(), 1);
+        assert!(reponse.header.recursion_available());
+
+        // Nom inconnu localement : relayé depuis l'amont avec l'identifiant d'origine
+        let reponse = interroger(adresse, 11, "b.com", DNS_TYPE_A).await;
+        assert_eq!(reponse.header.id, 11);
+        assert_eq!(reponse.header.rcode(), Rcode::NoError);
+        assert!(reponse.header.recursion_available());
+        assert_eq!(reponse.answers[0].rdata, tp7_dns::RData::A([2, 2, 2, 2].into()));
+
+        // Inconnu des deux serveurs : NXDOMAIN de l'amont relayé
+        let reponse = interroger(adresse, 12, "c.com", DNS_TYPE_A).await;
+        assert_eq!(reponse.header.rcode(), Rcode::NxDomain);
+    }
+
+    #[tokio::test]
+    async fn test_cache_des_reponses_amont() {
+        let amont = lancer(serveur_test(&zone("com", "b.com. 60 A 2.2.2.2\n")).await);
+        let serveur = serveur_test(&zone("a.com", "a.com. A 1.1.1.1\n")).await.avec_amont(amont, Duration::from_secs(2)).avec_cache(10);
+
+        let requete = DnsMessage::new_query(3, "b.com".to_string(), DNS_TYPE_A).unwrap();
+        assert_eq!(serveur.repondre(&requete, CLIENT).await.answers.len(), 1);
+        assert_eq!(serveur.repondre(&requete, CLIENT).await.answers.len(), 1);
+
+        let stats = serveur.cache.as_ref().unwrap().lock().unwrap().stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_reponse_tronquee_puis_tcp() {
+        let gros = format!("gros.com. TXT \"{}\" \"{}\"\n", "a".repeat(250), "b".repeat(250));
+        let adresse = lancer(serveur_test(&zone("gros.com", &gros)).await);
+
+        // Trop grand pour UDP : réponse vide avec le bit TC
+        let reponse = interroger(adresse, 1, "gros.com", tp7_dns::DNS_TYPE_TXT).await;
+        assert!(reponse.header.truncated());
+        assert!(reponse.answers.is_empty());
+
+        // En TCP, la réponse complète est reçue, et la connexion reste utilisable
+        let mut flux = TcpStream::connect(adresse).await.unwrap();
+        for id in [2, 3] {
+            let requete = DnsMessage::new_query(id, "gros.com".to_string(), tp7_dns::DNS_TYPE_TXT).unwrap();
+            write_tcp_message(&mut flux, &requete.to_bytes()).await.unwrap();
+            let reponse = DnsMessage::from_bytes(&read_tcp_message(&mut flux).await.unwrap()).unwrap();
+            assert_eq!(reponse.header.id, id);
+            assert!(!reponse.header.truncated());
+            assert_eq!(reponse.answers.len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_taille_udp_negociee_en_edns() {
+        let gros = format!("gros.com. TXT \"{}\" \"{}\"\n", "a".repeat(250), "b".repeat(250));
+        let adresse = lancer(serveur_test(&zone("gros.com", &gros)).await);
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut buffer = vec![0u8; EDNS_UDP_PAYLOAD as usize];
+
+        // Le client annonce 1232 octets : la réponse de ~550 octets tient en UDP
+        let mut requete = DnsMessage::new_query(1, "gros.com".to_string(), tp7_dns::DNS_TYPE_TXT).unwrap();
+        requete.set_edns(Edns::new(1232));
+        socket.send_to(&requete.to_bytes(), adresse).await.unwrap();
+        let (taille, _) = socket.recv_from(&mut buffer).await.unwrap();
+        let reponse = DnsMessage::from_bytes(&buffer[..taille]).unwrap();
+        assert!(taille > 512);
+        assert!(!reponse.header.truncated());
+        assert_eq!(reponse.answers.len(), 1);
+        assert_eq!(reponse.edns().unwrap().udp_payload_size, EDNS_UDP_PAYLOAD);
+
+        // Version EDNS inconnue : BADVERS
+        let mut edns = Edns::new(1232);
+        edns.version = 1;
+        requete.set_edns(edns);
+        socket.send_to(&requete.to_bytes(), adresse).await.unwrap();
+        let (taille, _) = socket.recv_from(&mut buffer).await.unwrap();
+        let reponse = DnsMessage::from_bytes(&buffer[..taille]).unwrap();
+        assert_eq!(reponse.rcode(), Rcode::BadVers);
+        assert!(reponse.answers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reponse_amont_sur_un_autre_nom_ignoree() {
+        // Faux amont : répond d'abord sur un autre nom, puis en changeant la casse du bon nom
+        let amont = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let adresse_amont = amont.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; EDNS_UDP_PAYLOAD as usize];
+            let (taille, client) = amont.recv_from(&mut buffer).await.unwrap();
+            let requete = DnsMessage::from_bytes(&buffer[..taille]).unwrap();
+            for (nom, ip) in [("autre.com", [6, 6, 6, 6]), ("B.com", [2, 2, 2, 2])] {
+                let mut question = requete.clone();
+                question.questions[0].qname = nom.to_string();
+                let mut reponse = DnsMessage::new_response(&question);
+                reponse.add_answer(DnsAnswer::new(nom.to_string(), 60, RData::A(ip.into())));
+                amont.send_to(&reponse.to_bytes(), client).await.unwrap();
+            }
+        });
+
+        let serveur = serveur_test("a.com. A 1.1.1.1\n").await.avec_amont(adresse_amont, Duration::from_secs(2)).avec_cache(10);
+        let requete = DnsMessage::new_query(5, "b.com".to_string(), DNS_TYPE_A).unwrap();
+        let reponse = serveur.repondre(&requete, CLIENT).await;
+        assert_eq!(reponse.answers[0].rdata, RData::A([2, 2, 2, 2].into()));
+        assert_eq!(serveur.cache.as_ref().unwrap().lock().unwrap().stats().entries, 1);
+    }
+
+    #[tokio::test]
+    async fn test_amont_muet_servfail() {
+        // Socket qui ne répond jamais
+        let muet = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let serveur = serveur_test("a.com. A 1.1.1.1\n").await;
+        let adresse = lancer(serveur.avec_amont(muet.local_addr().unwrap(), Duration::from_millis(200)));
+
+        let reponse = interroger(adresse, 7, "b.com", DNS_TYPE_A).await;
+        assert_eq!(reponse.header.id, 7);
+        assert_eq!(reponse.header.rcode(), Rcode::ServFail);
+    }
+
+    #[tokio::test]
+    async fn test_rcode_bien_forme() {
+        let serveur = serveur_test("a.com. A 1.1.1.1\n").await;
+
+        // Deux questions inconnues : FORMERR, et non un cumul de codes
+        let mut requete = DnsMessage::new_query(1, "x.com".to_string(), DNS_TYPE_A).unwrap();
+        requete.questions.push(tp7_dns::DnsQuestion::new("y.com".to_string(), DNS_TYPE_A).unwrap());
+        requete.header.qdcount = 2;
+        assert_eq!(serveur.repondre(&requete, CLIENT).await.rcode(), Rcode::FormErr);
+
+        // Opcode non géré : NOTIMP, opcode repris dans la réponse
+        let mut requete = DnsMessage::new_query(2, "a.com".to_string(), DNS_TYPE_A).unwrap();
+        requete.header.set_opcode(Opcode::Status);
+        let reponse = serveur.repondre(&requete, CLIENT).await;
+        assert_eq!(reponse.rcode(), Rcode::NotImp);
+        assert_eq!(reponse.header.opcode(), Opcode::Status);
+        assert!(reponse.header.is_response());
+    }
+
+    #[tokio::test]
+    async fn test_noms_insensibles_a_la_casse() {
+        let adresse = lancer(serveur_test(&zone("exemple.com", "www.exemple.com. A 1.1.1.1\n")).await);
+
+        let reponse = interroger(adresse, 1, "WWW.Exemple.COM", DNS_TYPE_A).await;
+        assert_eq!(reponse.header.rcode(), Rcode::NoError);
+        assert_eq!(reponse.questions[0].qname, "WWW.Exemple.COM");
+        assert_eq!(reponse.answers[0].name, "WWW.Exemple.COM");
+    }
+
+    #[tokio::test]
+    async fn test_jokers_et_chaines_cname() {
+        let enregistrements = "*.staging A 10.0.0.1\nweb CNAME app.staging\nboucle1 CNAME boucle2\nboucle2 CNAME boucle1\n";
+        let serveur = serveur_test(&zone("exemple.com", enregistrements)).await;
+
+        let requete = DnsMessage::new_query(1, "web.exemple.com".to_string(), DNS_TYPE_A).unwrap();
+        let reponse = serveur.repondre(&requete, CLIENT).await;
+        assert_eq!(reponse.rcode(), Rcode::NoError);
+        assert_eq!(reponse.answers.len(), 2);
+        assert_eq!(reponse.answers[0].rtype, tp7_dns::DNS_TYPE_CNAME);
+        assert_eq!(reponse.answers[1].name, "app.staging.exemple.com");
+
+        let requete = DnsMessage::new_query(2, "boucle1.exemple.com".to_string(), DNS_TYPE_A).unwrap();
+        assert_eq!(serveur.repondre(&requete, CLIENT).await.rcode(), Rcode::ServFail);
+    }
+
+    #[tokio::test]
+    async fn test_resolution_inverse_synthetisee() {
+        let enregistrements = "www A 192.168.1.101\nwww AAAA 2001:db8::101\ndns A 8.8.8.8\n";
+        let adresse = lancer(serveur_test(&zone("exemple.com", enregistrements)).await.avec_synthese_ptr());
+
+        for ip in ["192.168.1.101", "2001:db8::101"] {
+            let nom = tp7_dns::reverse_name(ip.parse().unwrap());
+            let reponse = interroger(adresse, 1, &nom, tp7_dns::DNS_TYPE_PTR).await;
+            assert_eq!(reponse.answers[0].rdata, tp7_dns::RData::Ptr("www.exemple.com".to_string()));
+            assert!(reponse.header.authoritative());
+        }
+
+        // Les autres adresses du bloc ne sont pas à nous : pas de NXDOMAIN faisant autorité
+        for nom in ["4.4.8.8.in-addr.arpa", "102.1.168.192.in-addr.arpa", "8.8.8.in-addr.arpa"] {
+            assert_eq!(interroger(adresse, 2, nom, tp7_dns::DNS_TYPE_PTR).await.rcode(), Rcode::Refused);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reponses_faisant_autorite() {
+        let serveur = serveur_test(&zone("exemple.com", "www A 1.1.1.1\nexterne CNAME ailleurs.org.\n")).await;
+        let repondre = |nom: &str, qtype| {
+            let requete = DnsMessage::new_query(1, nom.to_string(), qtype).unwrap();
+            let serveur = &serveur;
+            async move { serveur.repondre(&requete, CLIENT).await }
+        };
+
+        let reponse = repondre("www.exemple.com", DNS_TYPE_A).await;
+        assert!(reponse.header.authoritative());
+        assert_eq!((reponse.rcode(), reponse.answers.len(), reponse.authorities.len()), (Rcode::NoError, 1, 0));
+
+        // NODATA : le nom existe sans le type demandé, SOA en autorité avec le TTL négatif
+        let reponse = repondre("www.exemple.com", tp7_dns::DNS_TYPE_MX).await;
+        assert_eq!((reponse.rcode(), reponse.answers.len()), (Rcode::NoError, 0));
+        assert_eq!(reponse.authorities[0].rtype, tp7_dns::DNS_TYPE_SOA);
+        assert_eq!(reponse.authorities[0].ttl, 60);
+
+        let reponse = repondre("absent.exemple.com", DNS_TYPE_A).await;
+        assert_eq!(reponse.rcode(), Rcode::NxDomain);
+        assert!(reponse.header.authoritative());
+        assert_eq!(reponse.authorities[0].name, "exemple.com");
+
+        // CNAME vers une zone étrangère : pas de NXDOMAIN pour la cible
+        let reponse = repondre("externe.exemple.com", DNS_TYPE_A).await;
+        assert_eq!((reponse.rcode(), reponse.answers.len()), (Rcode::NoError, 1));
+
+        // Hors de nos zones, sans transfert : REFUSED
+        let reponse = repondre("exemple.org", DNS_TYPE_A).await;
+        assert_eq!(reponse.rcode(), Rcode::Refused);
+        assert!(!reponse.header.authoritative());
+    }
+
+    #[tokio::test]
+    async fn test_vues_selon_client() {
+        let zone = zone("exemple.com", "www A 203.0.113.10\n$VIEW labo 127.0.0.0/8\nwww A 10.0.0.10\n$ENDVIEW\n");
+        let serveur = Arc::new(serveur_test(&zone).await.avec_synthese_ptr());
+        let adresse = lancer_partage(Arc::clone(&serveur));
+
+        // Client local : vue labo, y compris pour la résolution inverse
+        let reponse = interroger(adresse, 1, "www.exemple.com", DNS_TYPE_A).await;
+        assert_eq!(reponse.answers[0].rdata, RData::A("10.0.0.10".parse().unwrap()));
+        let inverse = interroger(adresse, 2, "10.0.0.10.in-addr.arpa", tp7_dns::DNS_TYPE_PTR).await;
+        assert_eq!(inverse.answers.len(), 1);
+
+        // Autre client : enregistrements communs
+        let requete = DnsMessage::new_query(3, "www.exemple.com".to_string(), DNS_TYPE_A).unwrap();
+        let reponse = serveur.repondre(&requete, "192.0.2.1".parse().unwrap()).await;
+        assert_eq!(reponse.answers[0].rdata, RData::A("203.0.113.10".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_journal_des_requetes() {
+        let chemin = std::env::temp_dir().join(format!("tp7_requetes_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&chemin);
+        let journal = QueryLog::open(&chemin, TAILLE_JOURNAL_PAR_DEFAUT, 1).unwrap();
+        let serveur = serveur_test(&zone("exemple.com", "www A 10.0.0.1\n")).await.avec_journal_requetes(journal);
+        let adresse = lancer(serveur);
+
+        interroger(adresse, 1, "www.exemple.com", DNS_TYPE_A).await;
+        interroger(adresse, 2, "absent.exemple.com", tp7_dns::DNS_TYPE_MX).await;
+
+        let contenu = std::fs::read_to_string(&chemin).unwrap();
+        let entrees: Vec<QueryLogEntry> = contenu.lines().map(|ligne| serde_json::from_str(ligne).unwrap()).collect();
+        assert_eq!(entrees.len(), 2);
+        assert_eq!((entrees[0].qname.as_str(), entrees[0].rcode.as_str(), entrees[0].answers), ("www.exemple.com", "NOERROR", 1));
+        assert_eq!((entrees[1].qtype.as_str(), entrees[1].rcode.as_str()), ("MX", "NXDOMAIN"));
+        assert_eq!(entrees[1].client, CLIENT);
+        assert_eq!(entrees[1].protocol, "udp");
+        std::fs::remove_file(&chemin).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_metriques_http() {
+        let serveur = Arc::new(serveur_test(&zone("exemple.com", "www A 10.0.0.1\n")).await);
+        let adresse = lancer_partage(Arc::clone(&serveur));
+        let ecoute = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let adresse_http = ecoute.local_addr().unwrap();
+        tokio::spawn(Arc::clone(&serveur).servir_metriques(ecoute));
+
+        interroger(adresse, 1, "www.exemple.com", DNS_TYPE_A).await;
+        interroger(adresse, 2, "absent.exemple.com", DNS_TYPE_A).await;
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        socket.send_to(&[0u8; 3], adresse).await.unwrap();
+
+        let obtenir = |chemin: &'static str| async move {
+            let mut flux = TcpStream::connect(adresse_http).await.unwrap();
+            flux.write_all(format!("GET {} HTTP/1.1\r\nHost: test\r\n\r\n", chemin).as_bytes()).await.unwrap();
+            let mut reponse = String::new();
+            flux.read_to_string(&mut reponse).await.unwrap();
+            reponse
+        };
+        // Le paquet illisible est traité en tâche de fond : on attend qu'il soit compté
+        let mut reponse = obtenir("/metrics").await;
+        for _ in 0..50 {
+            if reponse.contains("dns_dropped_packets_total{reason=\"invalide\"} 1") {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            reponse = obtenir("/metrics").await;
+        }
+        assert!(reponse.starts_with("HTTP/1.1 200 OK"));
+        assert!(reponse.contains("dns_queries_total{qtype=\"A\"} 2"));
+        assert!(reponse.contains("dns_responses_total{rcode=\"NXDOMAIN\"} 1"));
+        assert!(reponse.contains("dns_response_duration_seconds_count 2"));
+        assert!(reponse.contains("dns_dropped_packets_total{reason=\"invalide\"} 1"));
+        assert!(obtenir("/autre").await.starts_with("HTTP/1.1 404"));
+    }
+
+    #[tokio::test]
+    async fn test_requete_mal_formee_formerr() {
+        let adresse = lancer(serveur_test(&zone("a.com", "a.com. A 1.1.1.1\n")).await);
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut buffer = vec![0u8; 512];
+
+        // Octets en trop après la question : FORMERR avec le même identifiant
+        let mut donnees = DnsMessage::new_query(42, "a.com".to_string(), DNS_TYPE_A).unwrap().to_bytes();
+        donnees.extend_from_slice(&[0xDE, 0xAD]);
+        socket.send_to(&donnees, adresse).await.unwrap();
+        let (taille, _) = socket.recv_from(&mut buffer).await.unwrap();
+        let reponse = DnsMessage::from_bytes(&buffer[..taille]).unwrap();
+        assert_eq!(reponse.header.id, 42);
+        assert_eq!(reponse.rcode(), Rcode::FormErr);
+        assert!(reponse.questions.is_empty());
+
+        // En-tête incomplet : ignoré, la requête suivante reçoit toujours sa réponse
+        socket.send_to(&[0, 1, 2], adresse).await.unwrap();
+        let reponse = interroger(adresse, 43, "a.com", DNS_TYPE_A).await;
+        assert_eq!(reponse.answers.len(), 1);
+    }
+
+    #[test]
+    fn test_zone_par_defaut() {
+        let table = ConfigServeur::depuis_arguments(std::iter::empty()).unwrap().charger_zone().unwrap();
+        for nom in ["exemple.com", "esgi", "test.local"] {
+            assert_eq!(table.find_zone(nom).unwrap().name, nom);
+        }
+        assert_eq!(table.find_zone("_sip._udp.exemple.com").unwrap().name, "exemple.com");
+    }
+
+    #[test]
+    fn test_arguments_concurrence() {
+        let arguments = ["--concurrence", "8"].iter().map(|a| a.to_string());
+        assert_eq!(ConfigServeur::depuis_arguments(arguments).unwrap().limite_concurrence, 8);
+
+        let arguments = ["--concurrence", "0"].iter().map(|a| a.to_string());
+        assert!(ConfigServeur::depuis_arguments(arguments).is_err());
+    }
+
+    /// Zone de test servie par un primaire, rafraîchie chaque seconde par les secondaires
+    fn zone_primaire(serial: u32, www: &str) -> String {
+        format!("$ORIGIN exemple.com.\n@ SOA ns admin {} 1 1 86400 60\n@ NS ns\nns A 10.0.0.53\nwww A {}\n", serial, www)
+    }
+
+    #[tokio::test]
+    async fn test_transferts_axfr_puis_ixfr() {
+        let chemin = fichier_temporaire("primaire", &zone_primaire(1, "10.0.0.1"));
+        let primaire = Arc::new(
+            serveur_test(&zone_primaire(1, "10.0.0.1")).await
+                .avec_transferts_autorises(vec!["127.0.0.1".parse().unwrap()]),
+        );
+        let adresse_primaire = lancer_partage(Arc::clone(&primaire));
+        let secondaire = ServeurDns::new("127.0.0.1:0", RecordTable::new()).await.unwrap();
+
+        // Premier transfert : zone complète
+        let diff = secondaire.transferer_zone("exemple.com", adresse_primaire, None).await.unwrap();
+        assert_eq!(diff.added.len(), 4);
+        assert_eq!(secondaire.table().get("www.exemple.com").unwrap()[0].rdata, RData::A("10.0.0.1".parse().unwrap()));
+
+        // Nouvelle version sur le primaire : seul l'incrément est transmis
+        std::fs::write(&chemin, zone_primaire(2, "10.0.0.2")).unwrap();
+        primaire.recharger_zone(&chemin).unwrap();
+        let mut requete = DnsMessage::new_query(9, "exemple.com".to_string(), DNS_TYPE_IXFR).unwrap();
+        requete.add_authority(secondaire.table().soa("exemple.com").unwrap().clone());
+        let transfert = ServeurDns::recevoir_transfert(&requete, adresse_primaire, None).await.unwrap();
+        assert!(matches!(transfert, Transfer::Incremental(ref versions) if versions.len() == 1));
+
+        let diff = secondaire.transferer_zone("exemple.com", adresse_primaire, None).await.unwrap();
+        assert_eq!((diff.added.len(), diff.removed.len()), (2, 2)); // SOA et www
+        assert_eq!(secondaire.table().get("www.exemple.com").unwrap()[0].rdata, RData::A("10.0.0.2".parse().unwrap()));
+
+        // Déjà à jour : rien ne change
+        assert!(secondaire.transferer_zone("exemple.com", adresse_primaire, None).await.unwrap().is_empty());
+        std::fs::remove_file(&chemin).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_transfert_refuse_hors_acl() {
+        let adresse = lancer(serveur_test(&zone_primaire(1, "10.0.0.1")).await);
+        let secondaire = ServeurDns::new("127.0.0.1:0", RecordTable::new()).await.unwrap();
+
+        let erreur = secondaire.transferer_zone("exemple.com", adresse, None).await.unwrap_err();
+        assert!(erreur.to_string().contains("REFUSED"));
+        assert!(secondaire.table().is_empty());
+
+        // En UDP, le SOA seul et le bit TC renvoient vers TCP
+        let reponse = interroger(adresse, 3, "exemple.com", DNS_TYPE_AXFR).await;
+        assert!(reponse.header.truncated());
+        assert_eq!(reponse.answers.len(), 1);
+        assert_eq!(reponse.answers[0].rtype, tp7_dns::DNS_TYPE_SOA);
+    }
+
+    #[tokio::test]
+    async fn test_secondaire_suit_le_primaire() {
+        let chemin = fichier_temporaire("suivi", &zone_primaire(1, "10.0.0.1"));
+        let primaire = Arc::new(
+            serveur_test(&zone_primaire(1, "10.0.0.1")).await
+                .avec_transferts_autorises(vec!["127.0.0.1".parse().unwrap()]),
+        );
+        let adresse_primaire = lancer_partage(Arc::clone(&primaire));
+        let secondaire = ServeurDns::new("127.0.0.1:0", RecordTable::new()).await.unwrap()
+            .avec_zone_secondaire("exemple.com", adresse_primaire, None);
+        let adresse_secondaire = lancer(secondaire);
+
+        // Attend que le secondaire serve la réponse attendue pour www
+        let attendre = |adresse_ip: &'static str| async move {
+            for id in 0..50 {
+                let reponse = interroger(adresse_secondaire, id, "www.exemple.com", DNS_TYPE_A).await;
+                if reponse.answers.first().is_some_and(|r| r.rdata == RData::A(adresse_ip.parse().unwrap())) {
+                    assert!(reponse.header.authoritative());
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            panic!("le secondaire ne sert pas {}", adresse_ip);
+        };
+        attendre("10.0.0.1").await;
+
+        std::fs::write(&chemin, zone_primaire(2, "10.0.0.2")).unwrap();
+        primaire.recharger_zone(&chemin).unwrap();
+        attendre("10.0.0.2").await;
+        std::fs::remove_file(&chemin).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mise_a_jour_dynamique() {
+        let chemin = fichier_temporaire("maj", &zone_primaire(1, "10.0.0.1"));
+        let serveur = ServeurDns::new("127.0.0.1:0", RecordTable::load(&chemin).unwrap()).await.unwrap()
+            .avec_mises_a_jour_autorisees(vec!["127.0.0.1".parse().unwrap()])
+            .avec_sauvegarde(chemin.clone());
+        let adresse = lancer(serveur);
+
+        let ci = DnsAnswer {
+            name: "ci.exemple.com".to_string(),
+            rtype: DNS_TYPE_A,
+            rclass: tp7_dns::DNS_CLASS_IN,
+            ttl: 60,
+            rdata: RData::A("10.0.0.9".parse().unwrap()),
+        };
+        let maj = Update::new("exemple.com").unwrap().require_name_absent("ci.exemple.com").add_record(ci);
+        let reponse = echanger(adresse, &maj.to_message(1)).await;
+        assert_eq!(reponse.header.opcode(), Opcode::Update);
+        assert_eq!(reponse.rcode(), Rcode::NoError);
+
+        // Le nom est servi, et la zone enregistrée avec un numéro de série incrémenté
+        let reponse = interroger(adresse, 2, "ci.exemple.com", DNS_TYPE_A).await;
+        assert_eq!(reponse.answers.len(), 1);
+        let enregistree = RecordTable::load(&chemin).unwrap();
+        assert!(enregistree.get("ci.exemple.com").is_some());
+        assert!(matches!(enregistree.soa("exemple.com").unwrap().rdata, RData::Soa { serial: 2, .. }));
+
+        // Le prérequis n'est plus satisfait
+        let reponse = echanger(adresse, &maj.to_message(3)).await;
+        assert_eq!(reponse.rcode(), Rcode::YxDomain);
+
+        // Zone inconnue
+        let reponse = echanger(adresse, &Update::new("autre.org").unwrap().to_message(4)).await;
+        assert_eq!(reponse.rcode(), Rcode::NotAuth);
+        std::fs::remove_file(&chemin).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mise_a_jour_avec_synthese_ptr() {
+        let contenu = format!("{}$VIEW interne 10.0.0.0/8\nwww A 10.1.1.1\n$ENDVIEW\n", zone_primaire(1, "10.0.0.1"));
+        let chemin = fichier_temporaire("maj_ptr", &contenu);
+        let serveur = ServeurDns::new("127.0.0.1:0", RecordTable::load(&chemin).unwrap()).await.unwrap()
+            .avec_synthese_ptr()
+            .avec_mises_a_jour_autorisees(vec!["127.0.0.1".parse().unwrap()])
+            .avec_sauvegarde(chemin.clone());
+        let adresse = lancer(serveur);
+        let ptr = tp7_dns::DNS_TYPE_PTR;
+        assert_eq!(interroger(adresse, 1, "1.0.0.10.in-addr.arpa", ptr).await.answers.len(), 1);
+
+        let maj = Update::new("exemple.com").unwrap().delete_rrset("www.exemple.com", DNS_TYPE_A);
+        assert_eq!(echanger(adresse, &maj.to_message(2)).await.rcode(), Rcode::NoError);
+
+        // Le PTR de l'adresse supprimée n'est plus servi, les autres sont toujours générés
+        assert!(interroger(adresse, 3, "1.0.0.10.in-addr.arpa", ptr).await.answers.is_empty());
+        assert_eq!(interroger(adresse, 4, "53.0.0.10.in-addr.arpa", ptr).await.answers.len(), 1);
+
+        // Le fichier enregistré ne contient ni PTR ni zone inverse générés, et garde sa vue
+        let enregistree = RecordTable::load(&chemin).unwrap();
+        assert!(enregistree.get("www.exemple.com").is_none());
+        assert!(enregistree.records().all(|record| !record.name.ends_with("in-addr.arpa")));
+        assert_eq!(enregistree.views().len(), 1);
+        assert!(matches!(enregistree.soa("exemple.com").unwrap().rdata, RData::Soa { serial: 2, .. }));
+
+        // Les zones inverses générées ne peuvent pas être modifiées
+        let inverse = Update::new("1.0.0.10.in-addr.arpa").unwrap().delete_name("1.0.0.10.in-addr.arpa");
+        assert_eq!(echanger(adresse, &inverse.to_message(5)).await.rcode(), Rcode::NotAuth);
+        std::fs::remove_file(&chemin).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mise_a_jour_refusee_hors_acl() {
+        let adresse = lancer(serveur_test(&zone_primaire(1, "10.0.0.1")).await);
+        let maj = Update::new("exemple.com").unwrap().delete_name("www.exemple.com");
+        let reponse = echanger(adresse, &maj.to_message(1)).await;
+        assert_eq!(reponse.rcode(), Rcode::Refused);
+        assert_eq!(interroger(adresse, 2, "www.exemple.com", DNS_TYPE_A).await.answers.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_requetes_signees_tsig() {
+        let cle = TsigKey::new("maj.exemple.com", b"secret partage".to_vec()).unwrap();
+        let serveur = serveur_test(&zone_primaire(1, "10.0.0.1")).await.avec_cle_tsig(cle.clone());
+        let adresse = lancer(serveur);
+        let maj = Update::new("exemple.com").unwrap().delete_name("www.exemple.com");
+
+        // Une mise à jour signée est acceptée sans ACL, et la réponse est signée
+        let (requete, mut signature) = sign_request(&maj.to_message(1).to_bytes(), &cle, unix_time()).unwrap();
+        let reponse = echanger_octets(adresse, &requete).await;
+        signature.verify_response(&reponse, unix_time()).unwrap();
+        assert_eq!(DnsMessage::from_bytes(&reponse).unwrap().rcode(), Rcode::NoError);
+        assert!(interroger(adresse, 2, "www.exemple.com", DNS_TYPE_A).await.answers.is_empty());
+
+        // Clé inconnue : NOTAUTH avec l'erreur BADKEY dans le TSIG de la réponse
+        let inconnue = TsigKey::new("autre.exemple.com", b"secret partage".to_vec()).unwrap();
+        let (requete, _) = sign_request(&maj.to_message(3).to_bytes(), &inconnue, unix_time()).unwrap();
+        let reponse = DnsMessage::from_bytes(&echanger_octets(adresse, &requete).await).unwrap();
+        assert_eq!(reponse.rcode(), Rcode::NotAuth);
+        let tsig = tp7_dns::Tsig::from_record(reponse.additionals.last().unwrap()).unwrap();
+        assert_eq!(tsig.error, TsigError::BadKey.code());
+
+        // Mauvais secret : BADSIG ; heure hors tolérance : BADTIME
+        let faussee = TsigKey::new("maj.exemple.com", b"autre secret".to_vec()).unwrap();
+        let (requete, _) = sign_request(&maj.to_message(4).to_bytes(), &faussee, unix_time()).unwrap();
+        let reponse = DnsMessage::from_bytes(&echanger_octets(adresse, &requete).await).unwrap();
+        let tsig = tp7_dns::Tsig::from_record(reponse.additionals.last().unwrap()).unwrap();
+        assert_eq!(tsig.error, TsigError::BadSig.code());
+        let (requete, _) = sign_request(&maj.to_message(5).to_bytes(), &cle, unix_time() - 3600).unwrap();
+        let reponse = DnsMessage::from_bytes(&echanger_octets(adresse, &requete).await).unwrap();
+        assert_eq!(reponse.rcode(), Rcode::NotAuth);
+        let tsig = tp7_dns::Tsig::from_record(reponse.additionals.last().unwrap()).unwrap();
+        assert_eq!(tsig.error, TsigError::BadTime.code());
+    }
+
+    #[tokio::test]
+    async fn test_transfert_signe_tsig() {
+        let cle = TsigKey::new("xfr", b"secret de transfert".to_vec()).unwrap();
+        let primaire = serveur_test(&zone_primaire(1, "10.0.0.1")).await.avec_cle_tsig(cle.clone());
+        let adresse_primaire = lancer(primaire);
+        let secondaire = ServeurDns::new("127.0.0.1:0", RecordTable::new()).await.unwrap();
+
+        // Aucune ACL : seule la signature autorise le transfert
+        let diff = secondaire.transferer_zone("exemple.com", adresse_primaire, Some(&cle)).await.unwrap();
+        assert!(!diff.is_empty());
+        assert!(secondaire.table().soa("exemple.com").is_some());
+        assert!(secondaire.transferer_zone("exemple.com", adresse_primaire, None).await.is_err());
+    }
+
+    #[test]
+    fn test_arguments_transferts() {
+        let arguments = ["--transfert-autorise", "10.0.0.2", "--secondaire", "exemple.com@10.0.0.1:53"];
+        let config = ConfigServeur::depuis_arguments(arguments.iter().map(|a| a.to_string())).unwrap();
+        assert_eq!(config.transferts_autorises, vec!["10.0.0.2".parse::<IpAddr>().unwrap()]);
+        assert_eq!(config.secondaires, vec![("exemple.com".to_string(), "10.0.0.1:53".parse().unwrap(), None)]);
+
+        let arguments = ["--tsig", "xfr:c2VjcmV0", "--secondaire", "esgi@10.0.0.1:53,xfr"];
+        let config = ConfigServeur::depuis_arguments(arguments.iter().map(|a| a.to_string())).unwrap();
+        assert_eq!(config.cles_tsig[0].name, "xfr");
+        assert_eq!(config.secondaires[0].2.as_deref(), Some("xfr"));
+
+        let arguments = ["--secondaire", "exemple.com"].iter().map(|a| a.to_string());
+        assert!(ConfigServeur::depuis_arguments(arguments).is_err());
+    }
+
+    #[test]
+    fn test_sauvegarde_distincte_du_fichier_de_zone() {
+        let zone = fichier_temporaire("zone_source", &zone_primaire(1, "10.0.0.1"));
+        let sauvegarde = zone.with_extension("sauvegarde");
+        let arguments = ["--zone", zone.to_str().unwrap(), "--sauvegarde", sauvegarde.to_str().unwrap()];
+        let config = ConfigServeur::depuis_arguments(arguments.iter().map(|a| a.to_string())).unwrap();
+        assert_eq!(config.fichier_sauvegarde.as_ref(), Some(&sauvegarde));
+
+        // Sans sauvegarde existante, le fichier de zone est chargé ; ensuite, la sauvegarde le remplace
+        assert!(matches!(config.charger_zone().unwrap().soa("exemple.com").unwrap().rdata, RData::Soa { serial: 1, .. }));
+        std::fs::write(&sauvegarde, zone_primaire(2, "10.0.0.2")).unwrap();
+        assert!(matches!(config.charger_zone().unwrap().soa("exemple.com").unwrap().rdata, RData::Soa { serial: 2, .. }));
+        std::fs::remove_file(&sauvegarde).unwrap();
+        std::fs::remove_file(&zone).unwrap();
+    }
+
+    #[test]
+    fn test_arguments_limitation() {
+        let config = ConfigServeur::depuis_arguments(std::iter::empty()).unwrap();
+        assert!(config.limitation.is_none());
+
+        let arguments = ["--rrl", "5", "--quota", "50"].iter().map(|a| a.to_string());
+        let limitation = ConfigServeur::depuis_arguments(arguments).unwrap().limitation.unwrap();
+        assert_eq!((limitation.responses_per_second, limitation.errors_per_second), (5, 5));
+        assert_eq!((limitation.queries_per_second, limitation.slip), (50, 2));
+
+        let arguments = ["--rrl-glissement", "x"].iter().map(|a| a.to_string());
+        assert!(ConfigServeur::depuis_arguments(arguments).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_limitation_debit_udp() {
+        let config = RateLimitConfig { responses_per_second: 1, slip: 1, ..RateLimitConfig::default() };
+        let serveur = Arc::new(serveur_test(&zone("exemple.com", "www A 10.0.0.1\n")).await.avec_limitation(config));
+        let adresse = lancer_partage(Arc::clone(&serveur));
+
+        // Au-delà du débit, la réponse identique est remplacée par une réponse tronquée vide
+        assert_eq!(interroger(adresse, 1, "www.exemple.com", DNS_TYPE_A).await.answers.len(), 1);
+        let limitee = interroger(adresse, 2, "www.exemple.com", DNS_TYPE_A).await;
+        assert!(limitee.header.truncated());
+        assert!(limitee.answers.is_empty());
+        // Le TCP n'est pas limité
+        let requete = DnsMessage::new_query(3, "www.exemple.com".to_string(), DNS_TYPE_A).unwrap();
+        let mut flux = TcpStream::connect(adresse).await.unwrap();
+        write_tcp_message(&mut flux, &requete.to_bytes()).await.unwrap();
+        let reponse = DnsMessage::from_bytes(&read_tcp_message(&mut flux).await.unwrap()).unwrap();
+        assert_eq!(reponse.answers.len(), 1);
+
+        let statistiques = serveur.statistiques();
+        assert_eq!(statistiques.requetes, 3);
+        let limitation = statistiques.limitation.unwrap();
+        assert_eq!((limitation.allowed, limitation.slipped, limitation.dropped), (1, 1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_quota_par_client() {
+        let config = RateLimitConfig { queries_per_second: 1, ..RateLimitConfig::default() };
+        let serveur = Arc::new(serveur_test(&zone("exemple.com", "www A 10.0.0.1\n")).await.avec_limitation(config));
+        let adresse = lancer_partage(Arc::clone(&serveur));
+
+        assert_eq!(interroger(adresse, 1, "www.exemple.com", DNS_TYPE_A).await.answers.len(), 1);
+        // Requête hors quota : aucune réponse
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let requete = DnsMessage::new_query(2, "www.exemple.com".to_string(), DNS_TYPE_A).unwrap();
+        socket.send_to(&requete.to_bytes(), adresse).await.unwrap();
+        let mut buffer = vec![0u8; 512];
+        assert!(tokio::time::timeout(Duration::from_millis(300), socket.recv_from(&mut buffer)).await.is_err());
+        assert_eq!(serveur.statistiques().limitation.unwrap().over_quota, 1);
+    }
+
+    #[tokio::test]
+    async fn test_rechargement_conserve_ancienne_zone_si_invalide() {
+        let chemin = fichier_temporaire("rechargement", "a.com. A 1.1.1.1\n");
+        let serveur = ServeurDns::new("127.0.0.1:0", RecordTable::load(&chemin).unwrap()).await.unwrap();
+
+        // Zone invalide : la table n'est pas remplacée
+        std::fs::write(&chemin, "a.com. A 999.1.1.1\n").unwrap();
+        assert!(serveur.recharger_zone(&chemin).is_err());
+        std::fs::write(&chemin, "a.com. CNAME b.com.\na.com. A 1.1.1.1\n").unwrap();
+        assert!(serveur.recharger_zone(&chemin).is_err());
+        assert!(serveur.table().get("a.com").is_some());
+
+        // Zone valide : la table est remplacée et la différence rapportée
+        std::fs::write(&chemin, "b.com. A 2.2.2.2\n").unwrap();
+        let diff = serveur.recharger_zone(&chemin).unwrap();
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.removed.len(), 1);
+        assert!(serveur.table().get("a.com").is_none());
+        assert!(serveur.table().get("b.com").is_some());
+
+        std::fs::remove_file(&chemin).unwrap();
+    }
+}
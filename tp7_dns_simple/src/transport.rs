@@ -0,0 +1,51 @@
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Taille maximale d'un message DNS transporté sur UDP (RFC 1035 §4.2.1)
+pub const MAX_UDP_SIZE: usize = 512;
+
+/// Lit un message DNS préfixé par sa longueur sur deux octets (RFC 1035 §4.2.2)
+pub async fn read_tcp_message<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<u8>, std::io::Error> {
+    let longueur = reader.read_u16().await? as usize;
+    let mut message = vec![0u8; longueur];
+    reader.read_exact(&mut message).await?;
+    Ok(message)
+}
+
+/// Écrit un message DNS préfixé par sa longueur sur deux octets (RFC 1035 §4.2.2)
+pub async fn write_tcp_message<W: AsyncWrite + Unpin>(writer: &mut W, message: &[u8]) -> Result<(), std::io::Error> {
+    let longueur = u16::try_from(message.len())
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "Message trop long pour TCP"))?;
+
+    // Longueur et message envoyés en une seule écriture
+    let mut trame = Vec::with_capacity(message.len() + 2);
+    trame.extend_from_slice(&longueur.to_be_bytes());
+    trame.extend_from_slice(message);
+    writer.write_all(&trame).await?;
+    writer.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_trames_tcp_aller_retour() {
+        let (mut client, mut serveur) = tokio::io::duplex(1024);
+
+        write_tcp_message(&mut client, b"premier").await.unwrap();
+        write_tcp_message(&mut client, &[0u8; 600]).await.unwrap();
+
+        assert_eq!(read_tcp_message(&mut serveur).await.unwrap(), b"premier");
+        assert_eq!(read_tcp_message(&mut serveur).await.unwrap().len(), 600);
+    }
+
+    #[tokio::test]
+    async fn test_trame_tronquee() {
+        let (mut client, mut serveur) = tokio::io::duplex(64);
+        client.write_all(&[0x00, 0x10, 1, 2, 3]).await.unwrap();
+        drop(client);
+
+        let erreur = read_tcp_message(&mut serveur).await.unwrap_err();
+        assert_eq!(erreur.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+}
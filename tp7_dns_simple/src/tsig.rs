@@ -0,0 +1,567 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::zone::cle;
+use crate::{lire_u16, DnsAnswer, DnsError, DnsMessage, DnsQuestion, RData, Rcode, DNS_CLASS_ANY, DNS_TYPE_TSIG};
+
+/// Seul algorithme de signature pris en charge
+pub const TSIG_ALGORITHM: &str = "hmac-sha256";
+
+/// Écart d'horloge toléré entre le signataire et le vérificateur, en secondes
+const FUDGE: u16 = 300;
+
+/// Longueur d'un MAC HMAC-SHA256
+const LONGUEUR_MAC: usize = 32;
+
+/// Clé secrète partagée servant à signer les messages (HMAC-SHA256)
+#[derive(Clone, PartialEq, Eq)]
+pub struct TsigKey {
+    /// Nom de la clé, porté par l'enregistrement TSIG
+    pub name: String,
+    secret: Vec<u8>,
+}
+
+impl TsigKey {
+    /// Crée une clé à partir de son nom et de son secret
+    pub fn new(name: &str, secret: Vec<u8>) -> Result<Self, DnsError> {
+        Ok(TsigKey { name: DnsQuestion::validate_domain_name(name)?, secret })
+    }
+
+    /// Lit une clé au format `nom:secret`, le secret étant encodé en base64
+    pub fn parse(texte: &str) -> Result<Self, String> {
+        let (nom, secret) = texte.split_once(':').ok_or("clé TSIG attendue au format nom:secret_base64")?;
+        let secret = BASE64.decode(secret.trim()).map_err(|e| format!("secret TSIG invalide: {}", e))?;
+        if secret.is_empty() {
+            return Err("secret TSIG vide".to_string());
+        }
+        TsigKey::new(nom, secret).map_err(|e| e.to_string())
+    }
+}
+
+impl fmt::Debug for TsigKey {
+    /// Le secret n'apparaît jamais dans les journaux
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TsigKey").field("name", &self.name).finish_non_exhaustive()
+    }
+}
+
+/// Trousseau des clés acceptées par un serveur, indexées par nom
+#[derive(Debug, Clone, Default)]
+pub struct TsigKeyring {
+    keys: HashMap<String, TsigKey>,
+}
+
+impl TsigKeyring {
+    /// Crée un trousseau vide
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ajoute ou remplace une clé
+    pub fn insert(&mut self, key: TsigKey) {
+        self.keys.insert(cle(&key.name), key);
+    }
+
+    /// Clé portant ce nom, sans tenir compte de la casse
+    pub fn get(&self, name: &str) -> Option<&TsigKey> {
+        self.keys.get(&cle(name))
+    }
+
+    /// Indique si le trousseau est vide
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// Contenu d'un enregistrement TSIG (RFC 8945)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tsig {
+    /// Nom de la clé (nom de l'enregistrement)
+    pub key_name: String,
+    pub algorithm: String,
+    /// Heure de signature, en secondes depuis l'époque Unix (48 bits)
+    pub time_signed: u64,
+    pub fudge: u16,
+    pub mac: Vec<u8>,
+    /// Identifiant du message au moment de la signature
+    pub original_id: u16,
+    /// Code d'erreur TSIG (BADSIG, BADKEY, BADTIME...)
+    pub error: u16,
+    pub other: Vec<u8>,
+}
+
+impl Tsig {
+    /// Lit un enregistrement TSIG ; `None` s'il n'en est pas un ou si ses données sont invalides
+    pub fn from_record(record: &DnsAnswer) -> Option<Self> {
+        let RData::Unknown(brut) = &record.rdata else {
+            return None;
+        };
+        if record.rtype != DNS_TYPE_TSIG {
+            return None;
+        }
+
+        // Le nom d'algorithme n'est jamais compressé : il se lit dans les données seules
+        let (algorithm, pos) = DnsQuestion::decode_domain_name(brut, 0).ok()?;
+        let champ = |debut: usize, longueur: usize| brut.get(debut..debut + longueur);
+        let time = champ(pos, 6)?;
+        let time_signed = time.iter().fold(0u64, |acc, &octet| acc << 8 | octet as u64);
+        let fudge = lire_u16(brut, pos + 6).ok()?;
+        let taille_mac = lire_u16(brut, pos + 8).ok()? as usize;
+        let mac = champ(pos + 10, taille_mac)?.to_vec();
+        let pos = pos + 10 + taille_mac;
+        let original_id = lire_u16(brut, pos).ok()?;
+        let error = lire_u16(brut, pos + 2).ok()?;
+        let taille_autre = lire_u16(brut, pos + 4).ok()? as usize;
+        let other = champ(pos + 6, taille_autre)?.to_vec();
+        if pos + 6 + taille_autre != brut.len() {
+            return None;
+        }
+
+        Some(Tsig {
+            key_name: record.name.clone(),
+            algorithm,
+            time_signed,
+            fudge,
+            mac,
+            original_id,
+            error,
+            other,
+        })
+    }
+
+    /// Construit l'enregistrement TSIG correspondant (classe ANY, TTL nul)
+    pub fn to_record(&self) -> DnsAnswer {
+        let mut brut = Vec::new();
+        DnsQuestion::write_domain_name(&self.algorithm, &mut brut);
+        brut.extend_from_slice(&self.time_signed.to_be_bytes()[2..]);
+        brut.extend_from_slice(&self.fudge.to_be_bytes());
+        brut.extend_from_slice(&(self.mac.len() as u16).to_be_bytes());
+        brut.extend_from_slice(&self.mac);
+        brut.extend_from_slice(&self.original_id.to_be_bytes());
+        brut.extend_from_slice(&self.error.to_be_bytes());
+        brut.extend_from_slice(&(self.other.len() as u16).to_be_bytes());
+        brut.extend_from_slice(&self.other);
+
+        DnsAnswer {
+            name: self.key_name.clone(),
+            rtype: DNS_TYPE_TSIG,
+            rclass: DNS_CLASS_ANY,
+            ttl: 0,
+            rdata: RData::Unknown(brut),
+        }
+    }
+}
+
+/// Échec de vérification d'une signature TSIG
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TsigError {
+    /// Enregistrement TSIG mal placé ou illisible
+    FormErr,
+    /// MAC incorrect
+    BadSig,
+    /// Clé ou algorithme inconnu
+    BadKey,
+    /// Heure de signature hors de la tolérance
+    BadTime,
+    /// Réponse d'erreur sans MAC, impossible à authentifier : à ignorer
+    Unsigned,
+}
+
+impl TsigError {
+    /// Valeur du champ Error de l'enregistrement TSIG (0 pour FORMERR, signalé dans l'en-tête)
+    pub fn code(self) -> u16 {
+        match self {
+            TsigError::FormErr => 0,
+            TsigError::BadSig => 16,
+            TsigError::BadKey => 17,
+            TsigError::BadTime => 18,
+            TsigError::Unsigned => 0,
+        }
+    }
+
+    /// RCODE de la réponse signalant l'erreur
+    pub fn rcode(self) -> Rcode {
+        match self {
+            TsigError::FormErr => Rcode::FormErr,
+            _ => Rcode::NotAuth,
+        }
+    }
+
+    /// Erreur correspondant au champ Error reçu d'un serveur
+    fn from_code(code: u16) -> Self {
+        match code {
+            17 => TsigError::BadKey,
+            18 => TsigError::BadTime,
+            _ => TsigError::BadSig,
+        }
+    }
+}
+
+impl fmt::Display for TsigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TsigError::FormErr => write!(f, "enregistrement TSIG mal formé"),
+            TsigError::BadSig => write!(f, "BADSIG (signature incorrecte)"),
+            TsigError::BadKey => write!(f, "BADKEY (clé inconnue)"),
+            TsigError::BadTime => write!(f, "BADTIME (heure de signature hors tolérance)"),
+            TsigError::Unsigned => write!(f, "réponse d'erreur TSIG non signée"),
+        }
+    }
+}
+
+impl std::error::Error for TsigError {}
+
+/// Requête signée refusée, avec de quoi construire la réponse d'erreur
+#[derive(Debug, Clone)]
+pub struct TsigFailure {
+    pub error: TsigError,
+    /// Enregistrement TSIG de la requête, s'il a pu être lu
+    request: Option<Box<Tsig>>,
+    /// Clé de la requête, connue uniquement pour BADTIME
+    key: Option<TsigKey>,
+}
+
+impl TsigFailure {
+    /// Encode `reponse` avec le RCODE de l'erreur et un TSIG portant son code. Seule la réponse
+    /// BADTIME est signée ; elle indique l'heure du serveur dans les données annexes.
+    pub fn encode_response(&self, reponse: &mut DnsMessage, now: u64) -> Vec<u8> {
+        reponse.set_rcode(self.error.rcode());
+        let octets = reponse.to_bytes();
+        let Some(requete) = &self.request else {
+            return octets;
+        };
+
+        let mut tsig = Tsig {
+            mac: Vec::new(),
+            error: self.error.code(),
+            original_id: reponse.header.id,
+            ..Tsig::clone(requete)
+        };
+        if let Some(key) = &self.key {
+            tsig.other = now.to_be_bytes()[2..].to_vec();
+            tsig.mac = calculer_mac(key, Some(&requete.mac), &octets, &tsig, true);
+        }
+        ajouter_tsig(&octets, &tsig)
+    }
+}
+
+/// Échange signé en cours : signe les réponses successives (serveur) ou les vérifie (client).
+/// Chaque message est chaîné au MAC du précédent, comme pour les transferts de zone.
+#[derive(Debug, Clone)]
+pub struct TsigContext {
+    key: TsigKey,
+    previous_mac: Vec<u8>,
+    responses: usize,
+}
+
+impl TsigContext {
+    /// Clé utilisée pour l'échange
+    pub fn key(&self) -> &TsigKey {
+        &self.key
+    }
+
+    /// Nombre d'octets ajoutés à un message par sa signature
+    pub fn overhead(&self) -> usize {
+        let tsig = Tsig {
+            key_name: self.key.name.clone(),
+            algorithm: TSIG_ALGORITHM.to_string(),
+            time_signed: 0,
+            fudge: FUDGE,
+            mac: vec![0; LONGUEUR_MAC],
+            original_id: 0,
+            error: 0,
+            other: Vec::new(),
+        };
+        tsig.to_record().to_bytes().len()
+    }
+
+    /// Signe une réponse encodée
+    pub fn sign_response(&mut self, message: &[u8], now: u64) -> Vec<u8> {
+        let tsig = Tsig {
+            key_name: self.key.name.clone(),
+            algorithm: TSIG_ALGORITHM.to_string(),
+            time_signed: now,
+            fudge: FUDGE,
+            mac: Vec::new(),
+            original_id: lire_u16(message, 0).unwrap_or(0),
+            error: 0,
+            other: Vec::new(),
+        };
+        let mac = calculer_mac(&self.key, Some(&self.previous_mac), message, &tsig, self.responses == 0);
+        self.previous_mac = mac.clone();
+        self.responses += 1;
+        ajouter_tsig(message, &Tsig { mac, ..tsig })
+    }
+
+    /// Vérifie la signature d'une réponse reçue pour cet échange. Le champ Error n'est pris en
+    /// compte qu'une fois le MAC vérifié ; une erreur sans MAC donne `TsigError::Unsigned`.
+    pub fn verify_response(&mut self, message: &[u8], now: u64) -> Result<(), TsigError> {
+        let (tsig, sans_tsig) = extraire_tsig(message)?.ok_or(TsigError::FormErr)?;
+        if cle(&tsig.key_name) != cle(&self.key.name) || !tsig.algorithm.eq_ignore_ascii_case(TSIG_ALGORITHM) {
+            return Err(TsigError::BadKey);
+        }
+        if tsig.error != 0 && tsig.mac.is_empty() {
+            return Err(TsigError::Unsigned);
+        }
+        verifier_mac(&self.key, Some(&self.previous_mac), &sans_tsig, &tsig, self.responses == 0)?;
+        if tsig.error != 0 {
+            return Err(TsigError::from_code(tsig.error));
+        }
+        verifier_heure(&tsig, now)?;
+        self.previous_mac = tsig.mac;
+        self.responses += 1;
+        Ok(())
+    }
+}
+
+/// Heure courante en secondes depuis l'époque Unix, pour signer et vérifier les messages
+pub fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duree| duree.as_secs())
+        .unwrap_or(0)
+}
+
+/// Signe une requête encodée avec `key`. Le contexte retourné vérifie ensuite la réponse.
+pub fn sign_request(message: &[u8], key: &TsigKey, now: u64) -> Result<(Vec<u8>, TsigContext), DnsError> {
+    let tsig = Tsig {
+        key_name: key.name.clone(),
+        algorithm: TSIG_ALGORITHM.to_string(),
+        time_signed: now,
+        fudge: FUDGE,
+        mac: Vec::new(),
+        original_id: lire_u16(message, 0)?,
+        error: 0,
+        other: Vec::new(),
+    };
+    let mac = calculer_mac(key, None, message, &tsig, true);
+    let signe = ajouter_tsig(message, &Tsig { mac: mac.clone(), ..tsig });
+    Ok((signe, TsigContext { key: key.clone(), previous_mac: mac, responses: 0 }))
+}
+
+/// Vérifie la signature d'une requête reçue. Retourne `Ok(None)` si elle n'est pas signée,
+/// ou le contexte qui servira à signer la réponse.
+pub fn verify_request(message: &[u8], keyring: &TsigKeyring, now: u64) -> Result<Option<TsigContext>, TsigFailure> {
+    let echec = |error, request: Option<&Tsig>, key: Option<&TsigKey>| TsigFailure {
+        error,
+        request: request.cloned().map(Box::new),
+        key: key.cloned(),
+    };
+    let (tsig, sans_tsig) = match extraire_tsig(message) {
+        Ok(Some(extrait)) => extrait,
+        Ok(None) => return Ok(None),
+        Err(error) => return Err(echec(error, None, None)),
+    };
+
+    let key = match keyring.get(&tsig.key_name) {
+        Some(key) if tsig.algorithm.eq_ignore_ascii_case(TSIG_ALGORITHM) => key,
+        _ => return Err(echec(TsigError::BadKey, Some(&tsig), None)),
+    };
+    verifier_mac(key, None, &sans_tsig, &tsig, true).map_err(|error| echec(error, Some(&tsig), None))?;
+    verifier_heure(&tsig, now).map_err(|error| echec(error, Some(&tsig), Some(key)))?;
+
+    Ok(Some(TsigContext { key: key.clone(), previous_mac: tsig.mac, responses: 0 }))
+}
+
+/// Cherche l'enregistrement TSIG, qui doit être le dernier du message. Retourne son contenu et
+/// le message tel qu'il était avant signature (sans TSIG, ARCOUNT et identifiant d'origine).
+/// Un TSIG hors de la section additionnelle est refusé (FORMERR).
+fn extraire_tsig(message: &[u8]) -> Result<Option<(Tsig, Vec<u8>)>, TsigError> {
+    let formerr = |_| TsigError::FormErr;
+    let qdcount = lire_u16(message, 4).map_err(formerr)?;
+    let arcount = lire_u16(message, 10).map_err(formerr)?;
+    let total = (6..12).step_by(2)
+        .map(|pos| lire_u16(message, pos).map(usize::from))
+        .sum::<Result<usize, DnsError>>()
+        .map_err(formerr)?;
+
+    let mut pos = 12;
+    for _ in 0..qdcount {
+        pos = DnsQuestion::decode_domain_name(message, pos).map_err(formerr)?.1 + 4;
+    }
+    let mut dernier = None;
+    for i in 0..total {
+        let (record, suite) = DnsMessage::parse_record(message, pos).map_err(formerr)?;
+        if record.rtype == DNS_TYPE_TSIG {
+            if i + 1 != total || arcount == 0 {
+                return Err(TsigError::FormErr);
+            }
+            dernier = Some((record, pos));
+        }
+        pos = suite;
+    }
+
+    let Some((record, debut)) = dernier else {
+        return Ok(None);
+    };
+    let tsig = Tsig::from_record(&record).ok_or(TsigError::FormErr)?;
+    let mut sans_tsig = message[..debut].to_vec();
+    sans_tsig[0..2].copy_from_slice(&tsig.original_id.to_be_bytes());
+    sans_tsig[10..12].copy_from_slice(&(arcount - 1).to_be_bytes());
+    Ok(Some((tsig, sans_tsig)))
+}
+
+/// Ajoute l'enregistrement TSIG à la fin d'un message encodé
+fn ajouter_tsig(message: &[u8], tsig: &Tsig) -> Vec<u8> {
+    let mut octets = message.to_vec();
+    let arcount = u16::from_be_bytes([octets[10], octets[11]]) + 1;
+    octets[10..12].copy_from_slice(&arcount.to_be_bytes());
+    octets.extend_from_slice(&tsig.to_record().to_bytes());
+    octets
+}
+
+/// Calcule le MAC d'un message (RFC 8945 §4.3)
+fn calculer_mac(key: &TsigKey, precedent: Option<&[u8]>, message: &[u8], tsig: &Tsig, variables_completes: bool) -> Vec<u8> {
+    preparer_hmac(key, precedent, message, tsig, variables_completes).finalize().into_bytes().to_vec()
+}
+
+/// Compare le MAC reçu au MAC attendu, en temps constant
+fn verifier_mac(key: &TsigKey, precedent: Option<&[u8]>, message: &[u8], tsig: &Tsig, variables_completes: bool) -> Result<(), TsigError> {
+    preparer_hmac(key, precedent, message, tsig, variables_completes)
+        .verify_slice(&tsig.mac)
+        .map_err(|_| TsigError::BadSig)
+}
+
+/// HMAC alimenté avec les données signées. Le MAC précédent (requête, ou message précédent
+/// d'une suite) est inclus en tête ; les messages suivant le premier ne couvrent que les horaires.
+fn preparer_hmac(key: &TsigKey, precedent: Option<&[u8]>, message: &[u8], tsig: &Tsig, variables_completes: bool) -> Hmac<Sha256> {
+    let mut hmac = Hmac::<Sha256>::new_from_slice(&key.secret).expect("HMAC accepte des clés de toute taille");
+    if let Some(precedent) = precedent {
+        hmac.update(&(precedent.len() as u16).to_be_bytes());
+        hmac.update(precedent);
+    }
+    hmac.update(message);
+
+    let mut variables = Vec::new();
+    if variables_completes {
+        DnsQuestion::write_domain_name(&tsig.key_name.to_ascii_lowercase(), &mut variables);
+        variables.extend_from_slice(&DNS_CLASS_ANY.to_be_bytes());
+        variables.extend_from_slice(&0u32.to_be_bytes());
+        DnsQuestion::write_domain_name(&tsig.algorithm.to_ascii_lowercase(), &mut variables);
+    }
+    variables.extend_from_slice(&tsig.time_signed.to_be_bytes()[2..]);
+    variables.extend_from_slice(&tsig.fudge.to_be_bytes());
+    if variables_completes {
+        variables.extend_from_slice(&tsig.error.to_be_bytes());
+        variables.extend_from_slice(&(tsig.other.len() as u16).to_be_bytes());
+        variables.extend_from_slice(&tsig.other);
+    }
+    hmac.update(&variables);
+    hmac
+}
+
+/// Vérifie que l'heure de signature est dans la tolérance annoncée
+fn verifier_heure(tsig: &Tsig, now: u64) -> Result<(), TsigError> {
+    if now.abs_diff(tsig.time_signed) > u64::from(tsig.fudge) {
+        return Err(TsigError::BadTime);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DNS_TYPE_A;
+
+    const MAINTENANT: u64 = 1_700_000_000;
+
+    fn cle_test() -> TsigKey {
+        TsigKey::parse("cle-test:c2VjcmV0LXBhcnRhZ2UtZGUtdGVzdA==").unwrap()
+    }
+
+    fn trousseau() -> TsigKeyring {
+        let mut trousseau = TsigKeyring::new();
+        trousseau.insert(cle_test());
+        trousseau
+    }
+
+    fn requete() -> Vec<u8> {
+        DnsMessage::new_query(1234, "exemple.com".to_string(), DNS_TYPE_A).unwrap().to_bytes()
+    }
+
+    #[test]
+    fn test_requete_et_reponse_signees() {
+        let (signee, mut client) = sign_request(&requete(), &cle_test(), MAINTENANT).unwrap();
+        let message = DnsMessage::from_bytes(&signee).unwrap();
+        let tsig = Tsig::from_record(message.additionals.last().unwrap()).unwrap();
+        assert_eq!(tsig.key_name, "cle-test");
+        assert_eq!(tsig.mac.len(), LONGUEUR_MAC);
+
+        let mut serveur = verify_request(&signee, &trousseau(), MAINTENANT + 10).unwrap().unwrap();
+        let reponse = DnsMessage::new_response(&message).to_bytes();
+        // Deux messages successifs, comme pour un transfert de zone
+        let premiere = serveur.sign_response(&reponse, MAINTENANT + 10);
+        let seconde = serveur.sign_response(&reponse, MAINTENANT + 11);
+        assert_eq!(client.verify_response(&premiere, MAINTENANT + 12), Ok(()));
+        assert_eq!(client.verify_response(&seconde, MAINTENANT + 12), Ok(()));
+
+        // Requête non signée
+        assert!(verify_request(&requete(), &trousseau(), MAINTENANT).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_erreurs_de_verification() {
+        let (mut signee, _) = sign_request(&requete(), &cle_test(), MAINTENANT).unwrap();
+        let erreur = |message: &[u8], now| verify_request(message, &trousseau(), now).unwrap_err().error;
+        assert_eq!(erreur(&signee, MAINTENANT + 301), TsigError::BadTime);
+
+        let inconnue = TsigKey::new("autre", b"secret".to_vec()).unwrap();
+        let (autre, _) = sign_request(&requete(), &inconnue, MAINTENANT).unwrap();
+        assert_eq!(erreur(&autre, MAINTENANT), TsigError::BadKey);
+
+        // TSIG en dernier mais dans la section réponse (ARCOUNT à 0)
+        let message = DnsMessage::from_bytes(&signee).unwrap();
+        let mut deplacee = DnsMessage::new_query(1234, "exemple.com".to_string(), DNS_TYPE_A).unwrap();
+        deplacee.add_answer(message.additionals.last().unwrap().clone());
+        let deplacee = deplacee.to_bytes();
+        assert_eq!((lire_u16(&deplacee, 6).unwrap(), lire_u16(&deplacee, 10).unwrap()), (1, 0));
+        assert_eq!(erreur(&deplacee, MAINTENANT), TsigError::FormErr);
+        let (_, mut client) = sign_request(&requete(), &cle_test(), MAINTENANT).unwrap();
+        assert_eq!(client.verify_response(&deplacee, MAINTENANT), Err(TsigError::FormErr));
+
+        // Message modifié après signature
+        signee[3] ^= 0x01;
+        assert_eq!(erreur(&signee, MAINTENANT), TsigError::BadSig);
+    }
+
+    #[test]
+    fn test_reponses_d_erreur() {
+        let (signee, mut client) = sign_request(&requete(), &cle_test(), MAINTENANT).unwrap();
+        let message = DnsMessage::from_bytes(&signee).unwrap();
+
+        // BADTIME : réponse signée portant l'heure du serveur
+        let echec = verify_request(&signee, &trousseau(), MAINTENANT + 1000).unwrap_err();
+        let octets = echec.encode_response(&mut DnsMessage::new_response(&message), MAINTENANT + 1000);
+        let reponse = DnsMessage::from_bytes(&octets).unwrap();
+        assert_eq!(reponse.rcode(), Rcode::NotAuth);
+        let tsig = Tsig::from_record(reponse.additionals.last().unwrap()).unwrap();
+        assert_eq!(tsig.error, 18);
+        assert_eq!(tsig.other.len(), 6);
+        assert_eq!(client.verify_response(&octets, MAINTENANT), Err(TsigError::BadTime));
+
+        // BADSIG : TSIG sans MAC
+        let mut falsifiee = signee.clone();
+        falsifiee[2] ^= 0x01;
+        let echec = verify_request(&falsifiee, &trousseau(), MAINTENANT).unwrap_err();
+        let octets = echec.encode_response(&mut DnsMessage::new_response(&message), MAINTENANT);
+        let tsig = Tsig::from_record(DnsMessage::from_bytes(&octets).unwrap().additionals.last().unwrap()).unwrap();
+        assert_eq!((tsig.error, tsig.mac.len()), (16, 0));
+        assert_eq!(client.verify_response(&octets, MAINTENANT), Err(TsigError::Unsigned));
+
+        // Erreur falsifiée avec un MAC quelconque : le MAC est vérifié avant le champ Error
+        let forgee = ajouter_tsig(&DnsMessage::new_response(&message).to_bytes(), &Tsig { mac: vec![0; LONGUEUR_MAC], ..tsig });
+        assert_eq!(client.verify_response(&forgee, MAINTENANT), Err(TsigError::BadSig));
+    }
+
+    #[test]
+    fn test_lecture_des_cles() {
+        assert!(TsigKey::parse("sans-secret").is_err());
+        assert!(TsigKey::parse("cle:pas du base64!").is_err());
+        assert!(!format!("{:?}", cle_test()).contains("secret"));
+    }
+}
@@ -0,0 +1,381 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::xfr::soa_serial;
+use crate::zone::cle;
+use crate::{DnsAnswer, DnsError, DnsHeader, DnsMessage, DnsQuestion, Opcode, RData, Rcode, RecordTable};
+use crate::{DNS_CLASS_ANY, DNS_CLASS_IN, DNS_CLASS_NONE, DNS_TYPE_ANY, DNS_TYPE_AXFR, DNS_TYPE_CNAME, DNS_TYPE_IXFR, DNS_TYPE_NS, DNS_TYPE_OPT, DNS_TYPE_SOA, DNS_TYPE_TSIG};
+
+/// Mise à jour dynamique d'une zone (RFC 2136) : des prérequis, puis des modifications
+#[derive(Debug, Clone, PartialEq)]
+pub struct Update {
+    /// Sommet de la zone modifiée (section zone)
+    pub zone: String,
+    /// Conditions vérifiées avant toute modification (section prérequis)
+    pub prerequisites: Vec<DnsAnswer>,
+    /// Ajouts et suppressions, appliqués dans l'ordre (section mise à jour)
+    pub updates: Vec<DnsAnswer>,
+}
+
+impl Update {
+    /// Crée une mise à jour vide de la zone `zone`
+    pub fn new(zone: &str) -> Result<Self, DnsError> {
+        Ok(Update {
+            zone: DnsQuestion::validate_domain_name(zone)?,
+            prerequisites: Vec::new(),
+            updates: Vec::new(),
+        })
+    }
+
+    /// Exige que le nom porte au moins un enregistrement
+    pub fn require_name_exists(mut self, name: &str) -> Self {
+        self.prerequisites.push(sans_donnees(name, DNS_CLASS_ANY, DNS_TYPE_ANY));
+        self
+    }
+
+    /// Exige que le nom ne porte aucun enregistrement
+    pub fn require_name_absent(mut self, name: &str) -> Self {
+        self.prerequisites.push(sans_donnees(name, DNS_CLASS_NONE, DNS_TYPE_ANY));
+        self
+    }
+
+    /// Exige que le nom porte des enregistrements de type `rtype`
+    pub fn require_rrset_exists(mut self, name: &str, rtype: u16) -> Self {
+        self.prerequisites.push(sans_donnees(name, DNS_CLASS_ANY, rtype));
+        self
+    }
+
+    /// Exige que le nom ne porte aucun enregistrement de type `rtype`
+    pub fn require_rrset_absent(mut self, name: &str, rtype: u16) -> Self {
+        self.prerequisites.push(sans_donnees(name, DNS_CLASS_NONE, rtype));
+        self
+    }
+
+    /// Ajoute un enregistrement à la zone
+    pub fn add_record(mut self, record: DnsAnswer) -> Self {
+        self.updates.push(record);
+        self
+    }
+
+    /// Supprime tous les enregistrements de type `rtype` du nom
+    pub fn delete_rrset(mut self, name: &str, rtype: u16) -> Self {
+        self.updates.push(sans_donnees(name, DNS_CLASS_ANY, rtype));
+        self
+    }
+
+    /// Supprime tous les enregistrements du nom
+    pub fn delete_name(mut self, name: &str) -> Self {
+        self.updates.push(sans_donnees(name, DNS_CLASS_ANY, DNS_TYPE_ANY));
+        self
+    }
+
+    /// Supprime un enregistrement précis
+    pub fn delete_record(mut self, record: DnsAnswer) -> Self {
+        self.updates.push(DnsAnswer { rclass: DNS_CLASS_NONE, ttl: 0, ..record });
+        self
+    }
+
+    /// Construit le message UPDATE correspondant
+    pub fn to_message(&self, id: u16) -> DnsMessage {
+        let mut header = DnsHeader::new(id, false);
+        header.set_opcode(Opcode::Update);
+        header.set_recursion_desired(false);
+        header.qdcount = 1;
+
+        let mut message = DnsMessage {
+            header,
+            questions: vec![DnsQuestion { qname: self.zone.clone(), qtype: DNS_TYPE_SOA, qclass: DNS_CLASS_IN }],
+            answers: Vec::new(),
+            authorities: Vec::new(),
+            additionals: Vec::new(),
+        };
+        for record in &self.prerequisites {
+            message.add_answer(record.clone());
+        }
+        for record in &self.updates {
+            message.add_authority(record.clone());
+        }
+        message
+    }
+
+    /// Extrait la mise à jour d'un message UPDATE ; FORMERR si la section zone est invalide
+    pub fn from_message(message: &DnsMessage) -> Result<Self, Rcode> {
+        match message.questions.as_slice() {
+            [zone] if zone.qtype == DNS_TYPE_SOA && zone.qclass == DNS_CLASS_IN => Ok(Update {
+                zone: zone.qname.clone(),
+                prerequisites: message.answers.clone(),
+                updates: message.authorities.clone(),
+            }),
+            _ => Err(Rcode::FormErr),
+        }
+    }
+
+    /// Vérifie les prérequis sur la table (RFC 2136 §3.2)
+    pub fn check_prerequisites(&self, table: &RecordTable) -> Result<(), Rcode> {
+        // Prérequis de classe IN : l'ensemble doit exister avec exactement ces données
+        let mut attendus: BTreeMap<(String, u16), HashSet<&RData>> = BTreeMap::new();
+
+        for prerequis in &self.prerequisites {
+            if prerequis.ttl != 0 {
+                return Err(Rcode::FormErr);
+            }
+            if !self.contains(&prerequis.name) {
+                return Err(Rcode::NotZone);
+            }
+            let existants = table.get(&prerequis.name).unwrap_or(&[]);
+            let present = match prerequis.rtype {
+                DNS_TYPE_ANY => !existants.is_empty(),
+                rtype => existants.iter().any(|record| record.rtype == rtype),
+            };
+
+            match prerequis.rclass {
+                DNS_CLASS_ANY | DNS_CLASS_NONE if !est_vide(prerequis) => return Err(Rcode::FormErr),
+                DNS_CLASS_ANY if !present => {
+                    return Err(if prerequis.rtype == DNS_TYPE_ANY { Rcode::NxDomain } else { Rcode::NxRrset });
+                }
+                DNS_CLASS_NONE if present => {
+                    return Err(if prerequis.rtype == DNS_TYPE_ANY { Rcode::YxDomain } else { Rcode::YxRrset });
+                }
+                DNS_CLASS_ANY | DNS_CLASS_NONE => {}
+                DNS_CLASS_IN if prerequis.rtype != DNS_TYPE_ANY => {
+                    attendus.entry((cle(&prerequis.name), prerequis.rtype)).or_default().insert(&prerequis.rdata);
+                }
+                _ => return Err(Rcode::FormErr),
+            }
+        }
+
+        for ((nom, rtype), donnees) in attendus {
+            let existantes: HashSet<&RData> = table.get(&nom).unwrap_or(&[])
+                .iter()
+                .filter(|record| record.rtype == rtype)
+                .map(|record| &record.rdata)
+                .collect();
+            if existantes != donnees {
+                return Err(Rcode::NxRrset);
+            }
+        }
+        Ok(())
+    }
+
+    /// Contrôle la section mise à jour avant toute modification (RFC 2136 §3.4.1)
+    pub fn prescan(&self) -> Result<(), Rcode> {
+        for record in &self.updates {
+            if !self.contains(&record.name) {
+                return Err(Rcode::NotZone);
+            }
+            let valide = match record.rclass {
+                DNS_CLASS_IN => !est_meta_type(record.rtype) && !est_vide(record),
+                DNS_CLASS_ANY => {
+                    record.ttl == 0 && est_vide(record) && (record.rtype == DNS_TYPE_ANY || !est_meta_type(record.rtype))
+                }
+                DNS_CLASS_NONE => record.ttl == 0 && !est_meta_type(record.rtype),
+                _ => false,
+            };
+            if !valide {
+                return Err(Rcode::FormErr);
+            }
+        }
+        Ok(())
+    }
+
+    /// Applique les modifications à la table (RFC 2136 §3.4.2) et retourne `true` si la zone a changé.
+    /// Le SOA et les NS du sommet ne peuvent pas être supprimés ; le numéro de série est incrémenté
+    /// si la mise à jour ne l'a pas elle-même avancé.
+    pub fn apply(&self, table: &mut RecordTable) -> bool {
+        let serial_initial = table.soa(&self.zone).and_then(soa_serial);
+        let mut modifie = false;
+
+        for record in &self.updates {
+            let sommet = cle(&record.name) == cle(&self.zone);
+            let existants: Vec<DnsAnswer> = table.get(&record.name).map(<[DnsAnswer]>::to_vec).unwrap_or_default();
+            let protege = |rtype: u16| sommet && (rtype == DNS_TYPE_SOA || rtype == DNS_TYPE_NS);
+
+            match record.rclass {
+                DNS_CLASS_IN if record.rtype == DNS_TYPE_SOA => {
+                    // Le SOA n'est remplacé qu'au sommet, par un numéro de série plus récent
+                    let actuel = table.soa(&self.zone).cloned();
+                    let plus_recent = match (soa_serial(record), actuel.as_ref().and_then(soa_serial)) {
+                        (Some(nouveau), Some(ancien)) => crate::serial_newer(nouveau, ancien),
+                        _ => false,
+                    };
+                    if sommet && plus_recent {
+                        if let Some(actuel) = actuel {
+                            table.remove(&actuel);
+                        }
+                        table.insert(record.clone());
+                        modifie = true;
+                    }
+                }
+                DNS_CLASS_IN => {
+                    // Un CNAME ne cohabite avec aucun autre type (RFC 1034 §3.6.2)
+                    let conflit = existants.iter().any(|existant| {
+                        (existant.rtype == DNS_TYPE_CNAME) != (record.rtype == DNS_TYPE_CNAME)
+                    });
+                    if conflit || existants.contains(record) {
+                        continue;
+                    }
+                    for existant in &existants {
+                        // Un CNAME remplace le précédent ; une donnée déjà présente change de TTL
+                        if record.rtype == DNS_TYPE_CNAME || (existant.rtype == record.rtype && existant.rdata == record.rdata) {
+                            table.remove(existant);
+                        }
+                    }
+                    table.insert(record.clone());
+                    modifie = true;
+                }
+                DNS_CLASS_ANY => {
+                    for existant in &existants {
+                        let vise = record.rtype == DNS_TYPE_ANY || existant.rtype == record.rtype;
+                        if vise && !protege(existant.rtype) {
+                            modifie |= table.remove(existant);
+                        }
+                    }
+                }
+                DNS_CLASS_NONE => {
+                    let dernier_ns = existants.iter().filter(|existant| existant.rtype == DNS_TYPE_NS).count() <= 1;
+                    if record.rtype == DNS_TYPE_SOA || (protege(record.rtype) && dernier_ns) {
+                        continue;
+                    }
+                    modifie |= table.remove(&DnsAnswer { rclass: DNS_CLASS_IN, ..record.clone() });
+                }
+                _ => {}
+            }
+        }
+
+        if modifie && table.soa(&self.zone).and_then(soa_serial) == serial_initial {
+            if let Some(mut soa) = table.soa(&self.zone).cloned() {
+                table.remove(&soa);
+                if let RData::Soa { serial, .. } = &mut soa.rdata {
+                    *serial = serial.wrapping_add(1);
+                }
+                table.insert(soa);
+            }
+        }
+        modifie
+    }
+
+    /// Indique si le nom appartient à la zone mise à jour
+    fn contains(&self, name: &str) -> bool {
+        let nom = cle(name);
+        let zone = cle(&self.zone);
+        nom == zone || nom.ends_with(&format!(".{}", zone))
+    }
+}
+
+/// Enregistrement sans données, servant de prérequis ou de suppression
+fn sans_donnees(name: &str, rclass: u16, rtype: u16) -> DnsAnswer {
+    DnsAnswer {
+        name: name.trim_end_matches('.').to_string(),
+        rtype,
+        rclass,
+        ttl: 0,
+        rdata: RData::Unknown(Vec::new()),
+    }
+}
+
+/// Indique si l'enregistrement a des données vides (RDLENGTH nul)
+fn est_vide(record: &DnsAnswer) -> bool {
+    matches!(&record.rdata, RData::Unknown(brut) if brut.is_empty())
+}
+
+/// Types qui ne désignent pas des données réelles et ne peuvent pas être ajoutés à une zone
+fn est_meta_type(rtype: u16) -> bool {
+    matches!(rtype, DNS_TYPE_ANY | DNS_TYPE_AXFR | DNS_TYPE_IXFR | DNS_TYPE_OPT | DNS_TYPE_TSIG)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DNS_TYPE_A;
+
+    fn table() -> RecordTable {
+        RecordTable::parse(
+            "$ORIGIN exemple.com.\n@ SOA ns admin 1 3600 600 86400 60\n@ NS ns\nns A 10.0.0.53\nwww A 10.0.0.1\nalias CNAME www\n",
+            None,
+        ).unwrap()
+    }
+
+    fn a(nom: &str, ip: &str) -> DnsAnswer {
+        DnsAnswer { name: nom.to_string(), rtype: DNS_TYPE_A, rclass: DNS_CLASS_IN, ttl: 60, rdata: RData::A(ip.parse().unwrap()) }
+    }
+
+    fn serial(table: &RecordTable) -> Option<u32> {
+        table.soa("exemple.com").and_then(soa_serial)
+    }
+
+    #[test]
+    fn test_message_update_aller_retour() {
+        let update = Update::new("exemple.com.").unwrap()
+            .require_name_absent("ci.exemple.com")
+            .add_record(a("ci.exemple.com", "10.0.0.9"))
+            .delete_rrset("www.exemple.com", DNS_TYPE_A);
+        let message = update.to_message(42);
+        assert_eq!(message.header.opcode(), Opcode::Update);
+
+        // Les données vides des prérequis et suppressions survivent au décodage
+        let decode = DnsMessage::from_bytes(&message.to_bytes()).unwrap();
+        assert_eq!(Update::from_message(&decode).unwrap(), update);
+
+        let mut requete = DnsMessage::new_query(1, "exemple.com".to_string(), DNS_TYPE_A).unwrap();
+        assert_eq!(Update::from_message(&requete), Err(Rcode::FormErr));
+        requete.questions.clear();
+        assert_eq!(Update::from_message(&requete), Err(Rcode::FormErr));
+    }
+
+    #[test]
+    fn test_prerequis() {
+        let table = table();
+        let verifier = |update: Update| update.check_prerequisites(&table);
+        let update = || Update::new("exemple.com").unwrap();
+
+        assert_eq!(verifier(update().require_name_exists("www.exemple.com")), Ok(()));
+        assert_eq!(verifier(update().require_name_exists("ci.exemple.com")), Err(Rcode::NxDomain));
+        assert_eq!(verifier(update().require_name_absent("www.exemple.com")), Err(Rcode::YxDomain));
+        assert_eq!(verifier(update().require_rrset_exists("www.exemple.com", crate::DNS_TYPE_TXT)), Err(Rcode::NxRrset));
+        assert_eq!(verifier(update().require_rrset_absent("www.exemple.com", DNS_TYPE_A)), Err(Rcode::YxRrset));
+        assert_eq!(verifier(update().require_name_exists("www.autre.org")), Err(Rcode::NotZone));
+
+        // Prérequis dépendant des valeurs : l'ensemble doit être identique
+        let mut exact = update();
+        exact.prerequisites.push(DnsAnswer { ttl: 0, ..a("www.exemple.com", "10.0.0.1") });
+        assert_eq!(verifier(exact.clone()), Ok(()));
+        exact.prerequisites.push(DnsAnswer { ttl: 0, ..a("www.exemple.com", "10.0.0.2") });
+        assert_eq!(verifier(exact), Err(Rcode::NxRrset));
+    }
+
+    #[test]
+    fn test_application_et_numero_de_serie() {
+        let mut table = table();
+        let update = Update::new("exemple.com").unwrap()
+            .add_record(a("ci.exemple.com", "10.0.0.9"))
+            .delete_record(a("www.exemple.com", "10.0.0.1"))
+            .add_record(a("alias.exemple.com", "10.0.0.8")) // ignoré : le nom porte un CNAME
+            .delete_name("exemple.com");             // le SOA et les NS du sommet restent
+        assert_eq!(update.prescan(), Ok(()));
+        assert!(update.apply(&mut table));
+
+        assert_eq!(table.get("ci.exemple.com").unwrap().len(), 1);
+        assert!(table.get("www.exemple.com").is_none());
+        assert_eq!(table.get("alias.exemple.com").unwrap()[0].rtype, DNS_TYPE_CNAME);
+        assert!(table.soa("exemple.com").is_some());
+        assert_eq!(serial(&table), Some(2));
+        assert!(table.validate().is_ok());
+
+        // Rien à changer : le numéro de série reste le même
+        let update = Update::new("exemple.com").unwrap().delete_rrset("absent.exemple.com", DNS_TYPE_A);
+        assert!(!update.apply(&mut table));
+        assert_eq!(serial(&table), Some(2));
+    }
+
+    #[test]
+    fn test_prescan() {
+        let update = Update::new("exemple.com").unwrap().add_record(a("www.autre.org", "10.0.0.1"));
+        assert_eq!(update.prescan(), Err(Rcode::NotZone));
+
+        let mut update = Update::new("exemple.com").unwrap().delete_rrset("www.exemple.com", DNS_TYPE_A);
+        update.updates[0].ttl = 60;
+        assert_eq!(update.prescan(), Err(Rcode::FormErr));
+
+        let update = Update::new("exemple.com").unwrap().add_record(DnsAnswer { rtype: DNS_TYPE_AXFR, ..a("www.exemple.com", "10.0.0.1") });
+        assert_eq!(update.prescan(), Err(Rcode::FormErr));
+    }
+}
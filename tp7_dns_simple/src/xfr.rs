@@ -0,0 +1,340 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::zone::cle;
+use crate::{DnsAnswer, DnsMessage, RData, RecordTable, ZoneDiff, ZoneError, DNS_TYPE_AXFR, DNS_TYPE_SOA};
+
+/// Nombre maximal de versions conservées par zone dans le journal
+const TAILLE_JOURNAL: usize = 64;
+
+/// Taille visée pour chaque message d'un transfert, bien en dessous de la limite TCP de 65535 octets
+const TAILLE_MESSAGE_TRANSFERT: usize = 16384;
+
+/// Modifications d'une zone entre deux numéros de série
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    pub old_soa: DnsAnswer,
+    pub new_soa: DnsAnswer,
+    /// Enregistrements retirés, hors SOA
+    pub removed: Vec<DnsAnswer>,
+    /// Enregistrements ajoutés, hors SOA
+    pub added: Vec<DnsAnswer>,
+}
+
+/// Historique des modifications de chaque zone, utilisé pour répondre aux IXFR (RFC 1995)
+#[derive(Debug, Clone, Default)]
+pub struct ZoneJournal {
+    entries: HashMap<String, VecDeque<JournalEntry>>,
+}
+
+impl ZoneJournal {
+    /// Crée un journal vide
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enregistre les modifications de chaque zone entre deux versions de la table.
+    /// Une zone modifiée sans changement de numéro de série perd son historique.
+    pub fn record(&mut self, ancienne: &RecordTable, nouvelle: &RecordTable) {
+        for apex in ancienne.zone_apexes() {
+            let cle = cle(&apex);
+            let (Some(ancien_soa), Some(nouveau_soa)) = (ancienne.soa(&apex), nouvelle.soa(&apex)) else {
+                self.entries.remove(&cle);
+                continue;
+            };
+
+            let avant = ancienne.zone_records(&apex);
+            let apres = nouvelle.zone_records(&apex);
+            let avant_set: HashSet<&DnsAnswer> = avant.iter().collect();
+            let apres_set: HashSet<&DnsAnswer> = apres.iter().collect();
+            let removed: Vec<DnsAnswer> = avant.iter()
+                .filter(|record| record.rtype != DNS_TYPE_SOA && !apres_set.contains(record))
+                .cloned()
+                .collect();
+            let added: Vec<DnsAnswer> = apres.iter()
+                .filter(|record| record.rtype != DNS_TYPE_SOA && !avant_set.contains(record))
+                .cloned()
+                .collect();
+
+            if soa_serial(ancien_soa) == soa_serial(nouveau_soa) {
+                if !removed.is_empty() || !added.is_empty() {
+                    self.entries.remove(&cle);
+                }
+                continue;
+            }
+
+            let historique = self.entries.entry(cle).or_default();
+            historique.push_back(JournalEntry {
+                old_soa: ancien_soa.clone(),
+                new_soa: nouveau_soa.clone(),
+                removed,
+                added,
+            });
+            if historique.len() > TAILLE_JOURNAL {
+                historique.pop_front();
+            }
+        }
+    }
+
+    /// Versions connues de la zone `apex`, de la plus ancienne à la plus récente
+    pub fn entries(&self, apex: &str) -> impl Iterator<Item = &JournalEntry> {
+        self.entries.get(&cle(apex)).into_iter().flatten()
+    }
+
+    /// Contenu d'une réponse IXFR depuis le numéro de série `serial`, ou `None` si
+    /// l'historique ne remonte pas jusque-là (le serveur se rabat alors sur un AXFR)
+    pub fn ixfr(&self, table: &RecordTable, apex: &str, serial: u32) -> Option<Vec<DnsAnswer>> {
+        let actuel = table.soa(apex)?.clone();
+        let historique = self.entries.get(&cle(apex))?;
+        let debut = historique.iter().position(|entry| soa_serial(&entry.old_soa) == Some(serial))?;
+
+        // La chaîne de versions doit aboutir au SOA actuellement servi
+        if soa_serial(&historique.back()?.new_soa) != soa_serial(&actuel) {
+            return None;
+        }
+
+        let mut records = vec![actuel.clone()];
+        for entry in historique.iter().skip(debut) {
+            records.push(entry.old_soa.clone());
+            records.extend(entry.removed.iter().cloned());
+            records.push(entry.new_soa.clone());
+            records.extend(entry.added.iter().cloned());
+        }
+        records.push(actuel);
+        Some(records)
+    }
+}
+
+/// Contenu d'une réponse de transfert de zone, une fois tous les messages reçus
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transfer {
+    /// Le secondaire est à jour : seul le SOA actuel est renvoyé
+    UpToDate(DnsAnswer),
+    /// Zone complète (AXFR, ou IXFR servi sous forme complète), SOA compris
+    Full(Vec<DnsAnswer>),
+    /// Suite de versions à appliquer dans l'ordre
+    Incremental(Vec<JournalEntry>),
+}
+
+impl Transfer {
+    /// Interprète les enregistrements reçus jusqu'ici pour un transfert de type `qtype`.
+    /// Retourne `Ok(None)` tant que le SOA final n'est pas arrivé.
+    pub fn parse(records: &[DnsAnswer], qtype: u16) -> Result<Option<Transfer>, ZoneError> {
+        let Some(premier) = records.first() else {
+            return Ok(None);
+        };
+        let serial = soa_serial(premier)
+            .ok_or_else(|| ZoneError::new(0, "le transfert ne commence pas par un SOA"))?;
+
+        let Some(second) = records.get(1) else {
+            return Ok((qtype != DNS_TYPE_AXFR).then(|| Transfer::UpToDate(premier.clone())));
+        };
+
+        // Forme complète : SOA, enregistrements, SOA
+        if second.rtype != DNS_TYPE_SOA || soa_serial(second) == Some(serial) {
+            let dernier = &records[records.len() - 1];
+            if records.len() < 2 || soa_serial(dernier) != Some(serial) {
+                return Ok(None);
+            }
+            return Ok(Some(Transfer::Full(records[..records.len() - 1].to_vec())));
+        }
+
+        // Forme incrémentale : SOA actuel, puis (ancien SOA, retraits, nouveau SOA, ajouts)*, SOA actuel
+        let mut entries = Vec::new();
+        let mut pos = 1;
+        loop {
+            let Some(ancien) = records.get(pos) else {
+                return Ok(None);
+            };
+            if ancien.rtype != DNS_TYPE_SOA {
+                return Err(ZoneError::new(0, "IXFR mal formé: SOA attendu avant les retraits"));
+            }
+            if soa_serial(ancien) == Some(serial) && !entries.is_empty() {
+                if pos + 1 != records.len() {
+                    return Err(ZoneError::new(0, "IXFR mal formé: enregistrements après le SOA final"));
+                }
+                return Ok(Some(Transfer::Incremental(entries)));
+            }
+            let removed = suite_hors_soa(records, pos + 1);
+            pos += 1 + removed.len();
+            let Some(nouveau) = records.get(pos) else {
+                return Ok(None);
+            };
+            let added = suite_hors_soa(records, pos + 1);
+            pos += 1 + added.len();
+            entries.push(JournalEntry {
+                old_soa: ancien.clone(),
+                new_soa: nouveau.clone(),
+                removed: removed.to_vec(),
+                added: added.to_vec(),
+            });
+        }
+    }
+
+    /// Applique le transfert à la zone `apex` de la table
+    pub fn apply(self, table: &mut RecordTable, apex: &str) -> Result<ZoneDiff, ZoneError> {
+        let avant = table.clone();
+        match self {
+            Transfer::UpToDate(_) => {}
+            Transfer::Full(records) => table.replace_zone(apex, records),
+            Transfer::Incremental(entries) => {
+                for entry in entries {
+                    let actuel = table.soa(apex).cloned();
+                    if actuel.as_ref().and_then(soa_serial) != soa_serial(&entry.old_soa) {
+                        return Err(ZoneError::new(0, format!(
+                            "IXFR pour {}: version de départ {:?} différente de la version locale",
+                            apex, soa_serial(&entry.old_soa)
+                        )));
+                    }
+                    if let Some(actuel) = actuel {
+                        table.remove(&actuel);
+                    }
+                    for record in &entry.removed {
+                        table.remove(record);
+                    }
+                    table.insert(entry.new_soa);
+                    for record in entry.added {
+                        table.insert(record);
+                    }
+                }
+            }
+        }
+        Ok(avant.diff(table))
+    }
+}
+
+/// Découpe un transfert en messages de réponse à `query`, chacun restant sous une taille raisonnable
+pub fn transfer_messages(query: &DnsMessage, records: Vec<DnsAnswer>) -> Vec<DnsMessage> {
+    let nouveau_message = || {
+        let mut message = DnsMessage::new_response(query);
+        message.header.set_authoritative(true);
+        message
+    };
+
+    let mut messages = Vec::new();
+    let mut courant = nouveau_message();
+    let mut taille = 0;
+    for record in records {
+        let longueur = record.to_bytes().len();
+        if !courant.answers.is_empty() && taille + longueur > TAILLE_MESSAGE_TRANSFERT {
+            messages.push(std::mem::replace(&mut courant, nouveau_message()));
+            taille = 0;
+        }
+        taille += longueur;
+        courant.add_answer(record);
+    }
+    messages.push(courant);
+    messages
+}
+
+/// Compare deux numéros de série selon l'arithmétique de la RFC 1982
+pub fn serial_newer(serial: u32, reference: u32) -> bool {
+    (serial.wrapping_sub(reference) as i32) > 0
+}
+
+/// Numéro de série d'un enregistrement SOA
+pub(crate) fn soa_serial(record: &DnsAnswer) -> Option<u32> {
+    match record.rdata {
+        RData::Soa { serial, .. } => Some(serial),
+        _ => None,
+    }
+}
+
+/// Enregistrements consécutifs à partir de `debut`, jusqu'au prochain SOA
+fn suite_hors_soa(records: &[DnsAnswer], debut: usize) -> &[DnsAnswer] {
+    let reste = records.get(debut..).unwrap_or(&[]);
+    let fin = reste.iter().position(|record| record.rtype == DNS_TYPE_SOA).unwrap_or(reste.len());
+    &reste[..fin]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DNS_TYPE_IXFR;
+
+    fn zone(serial: u32, www: &str) -> RecordTable {
+        RecordTable::parse(&format!(
+            "$ORIGIN exemple.com.\n@ SOA ns admin {} 3600 600 86400 60\n@ NS ns\nns A 10.0.0.53\nwww A {}\n",
+            serial, www
+        ), None).unwrap()
+    }
+
+    #[test]
+    fn test_axfr_encadre_par_le_soa() {
+        let records = zone(1, "10.0.0.1").axfr("exemple.com").unwrap();
+        assert_eq!(records.len(), 5);
+        assert_eq!(records[0].rtype, DNS_TYPE_SOA);
+        assert_eq!(records[4], records[0]);
+
+        let mut secondaire = RecordTable::new();
+        let transfert = Transfer::parse(&records, DNS_TYPE_AXFR).unwrap().unwrap();
+        let diff = transfert.apply(&mut secondaire, "exemple.com").unwrap();
+        assert_eq!(diff.added.len(), 4);
+        assert_eq!(secondaire.get("www.exemple.com").unwrap()[0].rdata, RData::A("10.0.0.1".parse().unwrap()));
+
+        // Tant que le SOA final manque, le transfert est incomplet
+        assert_eq!(Transfer::parse(&records[..4], DNS_TYPE_AXFR).unwrap(), None);
+        assert_eq!(Transfer::parse(&records[..1], DNS_TYPE_AXFR).unwrap(), None);
+    }
+
+    #[test]
+    fn test_journal_et_ixfr() {
+        let v1 = zone(1, "10.0.0.1");
+        let v2 = zone(2, "10.0.0.2");
+        let v3 = zone(3, "10.0.0.3");
+        let mut journal = ZoneJournal::new();
+        journal.record(&v1, &v2);
+        journal.record(&v2, &v3);
+        assert_eq!(journal.entries("exemple.com").count(), 2);
+
+        // Depuis la version 1 : deux deltas, puis le SOA actuel
+        let records = journal.ixfr(&v3, "exemple.com", 1).unwrap();
+        assert_eq!(records.len(), 1 + 2 * 4 + 1);
+        let Some(Transfer::Incremental(entries)) = Transfer::parse(&records, DNS_TYPE_IXFR).unwrap() else {
+            panic!("transfert incrémental attendu");
+        };
+        assert_eq!(entries.len(), 2);
+
+        let mut secondaire = v1.clone();
+        let diff = Transfer::Incremental(entries).apply(&mut secondaire, "exemple.com").unwrap();
+        assert_eq!(diff.removed.len(), 2); // SOA et www de la version 1
+        assert!(v3.diff(&secondaire).is_empty());
+
+        // Version inconnue : pas de réponse incrémentale possible
+        assert_eq!(journal.ixfr(&v3, "exemple.com", 7), None);
+        // Un incrément appliqué à la mauvaise version est refusé
+        let records = journal.ixfr(&v3, "exemple.com", 2).unwrap();
+        let transfert = Transfer::parse(&records, DNS_TYPE_IXFR).unwrap().unwrap();
+        assert!(transfert.apply(&mut v1.clone(), "exemple.com").is_err());
+    }
+
+    #[test]
+    fn test_modification_sans_changement_de_serie() {
+        let mut journal = ZoneJournal::new();
+        journal.record(&zone(1, "10.0.0.1"), &zone(2, "10.0.0.2"));
+        journal.record(&zone(2, "10.0.0.2"), &zone(2, "10.0.0.9"));
+        assert_eq!(journal.entries("exemple.com").count(), 0);
+    }
+
+    #[test]
+    fn test_decoupage_et_numeros_de_serie() {
+        let query = DnsMessage::new_query(7, "exemple.com".to_string(), DNS_TYPE_AXFR).unwrap();
+        let mut table = zone(1, "10.0.0.1");
+        for i in 0..2000 {
+            table.insert(DnsAnswer {
+                name: format!("h{}.exemple.com", i),
+                rtype: crate::DNS_TYPE_A,
+                rclass: crate::DNS_CLASS_IN,
+                ttl: 60,
+                rdata: RData::A("10.1.0.1".parse().unwrap()),
+            });
+        }
+        let messages = transfer_messages(&query, table.axfr("exemple.com").unwrap());
+        assert!(messages.len() > 1);
+        assert!(messages.iter().all(|m| m.header.authoritative() && m.to_bytes().len() < 65535));
+        assert_eq!(messages.iter().map(|m| m.answers.len()).sum::<usize>(), 2005);
+
+        assert!(serial_newer(2, 1));
+        assert!(!serial_newer(1, 1));
+        assert!(serial_newer(3, u32::MAX));
+    }
+}
@@ -0,0 +1,1066 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::net::IpAddr;
+use std::path::Path;
+
+use crate::{reverse_name, type_from_str, Cidr, DnsAnswer, DnsQuestion, RData};
+use crate::{DNS_TYPE_A, DNS_TYPE_AAAA, DNS_TYPE_CNAME, DNS_TYPE_MX, DNS_TYPE_NS, DNS_TYPE_PTR, DNS_TYPE_SOA, DNS_TYPE_SRV, DNS_TYPE_TXT};
+
+/// TTL appliqué lorsque ni l'enregistrement ni une directive $TTL ne le précisent
+const TTL_PAR_DEFAUT: u32 = 3600;
+
+/// Nombre maximal de CNAME suivis lors d'une résolution
+const MAX_CHAINE_CNAME: usize = 16;
+
+/// Erreur de lecture d'un fichier de zone, avec le numéro de ligne concerné (0 si global)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ZoneError {
+    pub(crate) fn new(line: usize, message: impl Into<String>) -> Self {
+        ZoneError { line, message: message.into() }
+    }
+}
+
+impl fmt::Display for ZoneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "ligne {}: {}", self.line, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ZoneError {}
+
+/// Différence entre deux versions d'une table d'enregistrements
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ZoneDiff {
+    pub added: Vec<DnsAnswer>,
+    pub removed: Vec<DnsAnswer>,
+}
+
+impl ZoneDiff {
+    /// Indique si les deux versions sont identiques
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Issue d'une résolution dans la table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LookupStatus {
+    /// Des enregistrements du type demandé ont été trouvés
+    Found,
+    /// Le nom existe mais ne porte pas le type demandé
+    NoData,
+    /// Le nom (ou la cible du dernier CNAME) n'existe pas
+    NxDomain,
+    /// La chaîne de CNAME boucle ou est trop longue
+    CnameLoop,
+}
+
+/// Résultat d'une résolution : chaîne de CNAME éventuelle suivie des enregistrements demandés
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolution {
+    /// Enregistrements de la section réponse, dans l'ordre de la chaîne
+    pub answers: Vec<DnsAnswer>,
+    pub status: LookupStatus,
+    /// Dernier nom atteint (le nom demandé s'il n'y a pas de CNAME)
+    pub final_name: String,
+}
+
+/// Table des enregistrements servis, indexée par nom de domaine
+#[derive(Debug, Clone, Default)]
+pub struct RecordTable {
+    records: BTreeMap<String, Vec<DnsAnswer>>,
+    /// Noms existants (porteurs de données ou ancêtres de tels noms), avec pour chacun le
+    /// nombre de noms porteurs de données qu'il couvre
+    existants: HashMap<String, usize>,
+    views: Vec<View>,
+}
+
+/// Vue (split-horizon) : enregistrements servis aux seuls clients de certains réseaux, à la
+/// place des enregistrements communs de même nom et de même type
+#[derive(Debug, Clone)]
+pub struct View {
+    pub name: String,
+    /// Réseaux des clients qui voient cette vue
+    pub networks: Vec<Cidr>,
+    records: RecordTable,
+}
+
+impl View {
+    /// Indique si `client` appartient à l'un des réseaux de la vue
+    pub fn contains(&self, client: IpAddr) -> bool {
+        self.networks.iter().any(|reseau| reseau.contains(client))
+    }
+
+    /// Enregistrements propres à la vue
+    pub fn records(&self) -> impl Iterator<Item = &DnsAnswer> {
+        self.records.records()
+    }
+}
+
+impl RecordTable {
+    /// Crée une table vide
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ajoute un enregistrement à la table
+    pub fn insert(&mut self, record: DnsAnswer) {
+        let cle_nom = cle(&record.name);
+        if !self.records.contains_key(&cle_nom) {
+            self.indexer(&cle_nom, true);
+        }
+        self.records.entry(cle_nom).or_default().push(record);
+    }
+
+    /// Enregistrements associés à un nom, sans tenir compte de la casse
+    pub fn get(&self, name: &str) -> Option<&[DnsAnswer]> {
+        self.records.get(&cle(name)).map(|records| records.as_slice())
+    }
+
+    /// Retire un enregistrement (comparé sans son TTL). Retourne `false` s'il était absent.
+    pub fn remove(&mut self, record: &DnsAnswer) -> bool {
+        let cle_nom = cle(&record.name);
+        let Some(records) = self.records.get_mut(&cle_nom) else {
+            return false;
+        };
+        let avant = records.len();
+        records.retain(|r| !(r.rtype == record.rtype && r.rclass == record.rclass && r.rdata == record.rdata));
+        let retire = records.len() != avant;
+        if records.is_empty() {
+            self.records.remove(&cle_nom);
+            self.indexer(&cle_nom, false);
+        }
+        retire
+    }
+
+    /// Compte (ou décompte) un nom porteur de données pour lui-même et chacun de ses ancêtres
+    fn indexer(&mut self, cle_nom: &str, ajout: bool) {
+        let mut nom = cle_nom;
+        while !nom.is_empty() {
+            if ajout {
+                *self.existants.entry(nom.to_string()).or_default() += 1;
+            } else if let Some(nombre) = self.existants.get_mut(nom) {
+                *nombre -= 1;
+                if *nombre == 0 {
+                    self.existants.remove(nom);
+                }
+            }
+            nom = nom.split_once('.').map(|(_, parent)| parent).unwrap_or("");
+        }
+    }
+
+    /// SOA porté par le sommet de zone `apex`
+    pub fn soa(&self, apex: &str) -> Option<&DnsAnswer> {
+        self.get(apex)?.iter().find(|record| record.rtype == DNS_TYPE_SOA)
+    }
+
+    /// Sommets des zones de la table (noms portant un SOA)
+    pub fn zone_apexes(&self) -> Vec<String> {
+        self.records().filter(|record| record.rtype == DNS_TYPE_SOA).map(|record| record.name.clone()).collect()
+    }
+
+    /// Enregistrements appartenant à la zone `apex`, SOA compris, hors sous-zones ayant leur propre SOA
+    pub fn zone_records(&self, apex: &str) -> Vec<DnsAnswer> {
+        let cle_apex = cle(apex);
+        self.records()
+            .filter(|record| self.find_zone(&record.name).is_some_and(|soa| cle(&soa.name) == cle_apex))
+            .cloned()
+            .collect()
+    }
+
+    /// Contenu d'un transfert complet (AXFR) : le SOA, les autres enregistrements, puis le SOA à nouveau
+    pub fn axfr(&self, apex: &str) -> Option<Vec<DnsAnswer>> {
+        let soa = self.soa(apex)?.clone();
+        let mut records = vec![soa.clone()];
+        records.extend(self.zone_records(apex).into_iter().filter(|record| record.rtype != DNS_TYPE_SOA));
+        records.push(soa);
+        Some(records)
+    }
+
+    /// Remplace tout le contenu de la zone `apex` par `records`
+    pub fn replace_zone(&mut self, apex: &str, records: Vec<DnsAnswer>) {
+        for record in self.zone_records(apex) {
+            self.remove(&record);
+        }
+        for record in records {
+            self.insert(record);
+        }
+    }
+
+    /// Enregistrements d'un nom, synthétisés depuis un joker si le nom n'existe pas (RFC 4592).
+    /// Les enregistrements retournés portent le nom demandé. `None` si le nom n'existe pas ;
+    /// un nœud vide (ancêtre d'autres noms sans données propres) donne une liste vide.
+    pub fn lookup(&self, name: &str) -> Option<Vec<DnsAnswer>> {
+        let renommer = |records: &[DnsAnswer]| -> Vec<DnsAnswer> {
+            records
+                .iter()
+                .map(|record| DnsAnswer { name: name.trim_end_matches('.').to_string(), ..record.clone() })
+                .collect()
+        };
+
+        let cle_nom = cle(name);
+        if let Some(records) = self.records.get(&cle_nom) {
+            return Some(renommer(records));
+        }
+        if self.existe(&cle_nom) {
+            return Some(Vec::new());
+        }
+
+        // Le joker applicable est celui de l'ancêtre existant le plus proche (closest encloser)
+        let mut ancetre = cle_nom.as_str();
+        loop {
+            let parent = ancetre.split_once('.').map(|(_, parent)| parent).unwrap_or("");
+            if parent.is_empty() || self.existe(parent) {
+                let joker = if parent.is_empty() { "*".to_string() } else { format!("*.{}", parent) };
+                return self.records.get(&joker).map(|records| renommer(records));
+            }
+            ancetre = parent;
+        }
+    }
+
+    /// Résout `name` pour le type `qtype` en suivant les CNAME présents dans la table
+    pub fn resolve(&self, name: &str, qtype: u16) -> Resolution {
+        let mut answers = Vec::new();
+        let mut nom = name.trim_end_matches('.').to_string();
+        let mut visites = HashSet::new();
+
+        loop {
+            if !visites.insert(cle(&nom)) || visites.len() > MAX_CHAINE_CNAME {
+                return Resolution { answers, status: LookupStatus::CnameLoop, final_name: nom };
+            }
+            let Some(records) = self.lookup(&nom) else {
+                return Resolution { answers, status: LookupStatus::NxDomain, final_name: nom };
+            };
+
+            let demandes: Vec<DnsAnswer> = records.iter().filter(|record| record.rtype == qtype).cloned().collect();
+            if !demandes.is_empty() {
+                answers.extend(demandes);
+                return Resolution { answers, status: LookupStatus::Found, final_name: nom };
+            }
+
+            match records.into_iter().find(|record| record.rtype == DNS_TYPE_CNAME) {
+                Some(cname) => {
+                    let RData::Cname(cible) = &cname.rdata else {
+                        return Resolution { answers, status: LookupStatus::NoData, final_name: nom };
+                    };
+                    nom = cible.clone();
+                    answers.push(cname);
+                }
+                None => return Resolution { answers, status: LookupStatus::NoData, final_name: nom },
+            }
+        }
+    }
+
+    /// SOA de la zone la plus proche contenant `name` (le nom lui-même ou un ancêtre),
+    /// `None` si le nom n'appartient à aucune zone de la table
+    pub fn find_zone(&self, name: &str) -> Option<&DnsAnswer> {
+        let cle_nom = cle(name);
+        let mut candidat = cle_nom.as_str();
+        loop {
+            let soa = self
+                .records
+                .get(candidat)
+                .and_then(|records| records.iter().find(|record| record.rtype == DNS_TYPE_SOA));
+            if soa.is_some() {
+                return soa;
+            }
+            if candidat.is_empty() {
+                return None;
+            }
+            candidat = candidat.split_once('.').map(|(_, parent)| parent).unwrap_or("");
+        }
+    }
+
+    /// Ajoute un PTR pour chaque enregistrement A et AAAA dont le nom inverse ne porte pas
+    /// déjà de PTR explicite. Les jokers sont ignorés. Retourne le nombre de PTR ajoutés.
+    /// Hors d'une zone inverse déclarée, le serveur ne fait autorité que sur le nom inverse
+    /// exact, qui reçoit le SOA et les NS de la zone de l'enregistrement d'origine : les autres
+    /// adresses du même bloc restent hors de nos zones.
+    pub fn synthesize_ptr(&mut self) -> usize {
+        let mut synthetises: Vec<DnsAnswer> = Vec::new();
+        let mut zones_inverses: Vec<DnsAnswer> = Vec::new();
+        for record in self.records() {
+            let ip = match record.rdata {
+                RData::A(ip) => IpAddr::V4(ip),
+                RData::Aaaa(ip) => IpAddr::V6(ip),
+                _ => continue,
+            };
+            if record.name.starts_with('*') {
+                continue;
+            }
+            let nom_inverse = reverse_name(ip);
+            let explicite = self
+                .get(&nom_inverse)
+                .is_some_and(|records| records.iter().any(|r| r.rtype == DNS_TYPE_PTR));
+            if explicite {
+                continue;
+            }
+            if self.find_zone(&nom_inverse).is_none() && !zones_inverses.iter().any(|r| r.name == nom_inverse) {
+                if let Some(soa) = self.find_zone(&record.name) {
+                    for origine in self.get(&soa.name).unwrap_or_default() {
+                        if origine.rtype == DNS_TYPE_SOA || origine.rtype == DNS_TYPE_NS {
+                            zones_inverses.push(DnsAnswer { name: nom_inverse.clone(), ..origine.clone() });
+                        }
+                    }
+                }
+            }
+            let ptr = DnsAnswer::new(nom_inverse, record.ttl, RData::Ptr(record.name.clone()));
+            if !synthetises.iter().any(|r| r.name == ptr.name && r.rdata == ptr.rdata) {
+                synthetises.push(ptr);
+            }
+        }
+
+        let nombre = synthetises.len();
+        for record in zones_inverses.into_iter().chain(synthetises) {
+            self.insert(record);
+        }
+        nombre
+    }
+
+    /// Indique si un nom existe : il porte des données ou a des descendants
+    fn existe(&self, cle_nom: &str) -> bool {
+        if cle_nom.is_empty() {
+            return !self.records.is_empty();
+        }
+        self.existants.contains_key(cle_nom)
+    }
+
+    /// Vues déclarées, dans l'ordre du fichier de zone
+    pub fn views(&self) -> &[View] {
+        &self.views
+    }
+
+    /// Première vue (dans l'ordre de déclaration) dont un réseau contient `client`
+    pub fn view_for(&self, client: IpAddr) -> Option<&View> {
+        self.views.iter().find(|vue| vue.contains(client))
+    }
+
+    /// Table vue par les clients de `view` : les enregistrements communs, dont ceux de même nom
+    /// et de même type que ceux de la vue sont remplacés. Un CNAME, d'un côté ou de l'autre,
+    /// remplace tout le nom. La table obtenue ne contient aucune vue.
+    pub fn with_view(&self, view: &View) -> RecordTable {
+        let mut table = RecordTable { records: self.records.clone(), existants: self.existants.clone(), views: Vec::new() };
+        for (cle_nom, remplacants) in &view.records.records {
+            if !table.records.contains_key(cle_nom) {
+                table.indexer(cle_nom, true);
+            }
+            let cname = remplacants.iter().any(|record| record.rtype == DNS_TYPE_CNAME);
+            let communs = table.records.entry(cle_nom.clone()).or_default();
+            communs.retain(|commun| {
+                !cname && commun.rtype != DNS_TYPE_CNAME && !remplacants.iter().any(|record| record.rtype == commun.rtype)
+            });
+            communs.extend(remplacants.iter().cloned());
+        }
+        table
+    }
+
+    /// Index de la vue `nom`, créée avec `reseaux` si elle n'existe pas encore
+    fn ouvrir_vue(&mut self, nom: &str, reseaux: Vec<Cidr>) -> Result<usize, String> {
+        if let Some(index) = self.views.iter().position(|vue| vue.name.eq_ignore_ascii_case(nom)) {
+            let vue = &mut self.views[index];
+            for reseau in reseaux {
+                if !vue.networks.contains(&reseau) {
+                    vue.networks.push(reseau);
+                }
+            }
+            return Ok(index);
+        }
+        if reseaux.is_empty() {
+            return Err(format!("la vue {} ne désigne aucun réseau", nom));
+        }
+        self.views.push(View { name: nom.to_string(), networks: reseaux, records: RecordTable::new() });
+        Ok(self.views.len() - 1)
+    }
+
+    /// Enregistrements communs puis ceux de chaque vue, avec le nom de leur vue
+    fn entries(&self) -> impl Iterator<Item = (Option<&str>, &DnsAnswer)> {
+        self.records().map(|record| (None, record)).chain(
+            self.views
+                .iter()
+                .flat_map(|vue| vue.records().map(move |record| (Some(vue.name.as_str()), record))),
+        )
+    }
+
+    /// Parcourt tous les enregistrements communs (hors vues), triés par nom
+    pub fn records(&self) -> impl Iterator<Item = &DnsAnswer> {
+        self.records.values().flatten()
+    }
+
+    /// Nombre total d'enregistrements communs
+    pub fn len(&self) -> usize {
+        self.records.values().map(|records| records.len()).sum()
+    }
+
+    /// Indique si la table ne contient aucun enregistrement commun
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Vérifie la cohérence de la table : un nom portant un CNAME ne peut porter aucune autre donnée,
+    /// et chaque sommet de zone porte un seul SOA et au moins un NS. Chaque vue est vérifiée
+    /// une fois combinée aux enregistrements communs.
+    pub fn validate(&self) -> Result<(), ZoneError> {
+        for vue in &self.views {
+            self.with_view(vue)
+                .validate()
+                .map_err(|e| ZoneError::new(0, format!("vue {}: {}", vue.name, e.message)))?;
+        }
+
+        for (nom, records) in &self.records {
+            let soas = records.iter().filter(|record| record.rtype == DNS_TYPE_SOA).count();
+            if soas > 1 {
+                return Err(ZoneError::new(0, format!("{} porte plusieurs SOA", nom)));
+            }
+            if soas == 1 && !records.iter().any(|record| record.rtype == DNS_TYPE_NS) {
+                return Err(ZoneError::new(0, format!("la zone {} n'a aucun NS", nom)));
+            }
+
+            let cnames = records.iter().filter(|record| record.rtype == DNS_TYPE_CNAME).count();
+            if cnames > 1 {
+                return Err(ZoneError::new(0, format!("{} porte plusieurs CNAME", nom)));
+            }
+            if cnames == 1 && records.len() > 1 {
+                return Err(ZoneError::new(0, format!("{} porte un CNAME et d'autres données", nom)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Calcule les enregistrements ajoutés et retirés pour passer de `self` à `nouvelle`,
+    /// vues comprises
+    pub fn diff(&self, nouvelle: &RecordTable) -> ZoneDiff {
+        let anciens: HashSet<(Option<&str>, &DnsAnswer)> = self.entries().collect();
+        let nouveaux: HashSet<(Option<&str>, &DnsAnswer)> = nouvelle.entries().collect();
+
+        ZoneDiff {
+            added: nouvelle.entries().filter(|entree| !anciens.contains(entree)).map(|(_, record)| record.clone()).collect(),
+            removed: self.entries().filter(|entree| !nouveaux.contains(entree)).map(|(_, record)| record.clone()).collect(),
+        }
+    }
+
+    /// Lit un fichier maître au format RFC 1035
+    pub fn load(path: &Path) -> Result<Self, ZoneError> {
+        let texte = std::fs::read_to_string(path)
+            .map_err(|e| ZoneError::new(0, format!("lecture de {} impossible: {}", path.display(), e)))?;
+        Self::parse(&texte, None)
+    }
+
+    /// Parse le contenu d'un fichier maître ($ORIGIN, $TTL, noms relatifs, parenthèses, commentaires).
+    /// `origine` sert d'origine initiale tant qu'aucune directive $ORIGIN n'est rencontrée.
+    /// Les enregistrements qui suivent `$VIEW nom réseau...` appartiennent à la vue `nom`,
+    /// jusqu'à `$ENDVIEW` ou la vue suivante.
+    pub fn parse(texte: &str, origine: Option<&str>) -> Result<Self, ZoneError> {
+        let mut table = RecordTable::new();
+        let mut origine = origine.map(|nom| nom.trim_end_matches('.').to_string());
+        let mut ttl_defaut: Option<u32> = None;
+        let mut dernier_proprietaire: Option<String> = None;
+        let mut vue: Option<usize> = None;
+
+        for ligne in lignes_logiques(texte)? {
+            let numero = ligne.numero;
+            let mut jetons = ligne.jetons.into_iter().peekable();
+            let Some(premier) = jetons.peek().cloned() else {
+                continue;
+            };
+
+            // Directives
+            if !ligne.indentee && !premier.guillemets && premier.texte.starts_with('$') {
+                jetons.next();
+                let directive = premier.texte.to_ascii_uppercase();
+                if directive == "$VIEW" {
+                    let nom = jetons.next().ok_or_else(|| ZoneError::new(numero, "$VIEW sans nom"))?.texte;
+                    let reseaux = jetons
+                        .map(|jeton| jeton.texte.parse::<Cidr>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|e| ZoneError::new(numero, e))?;
+                    vue = Some(table.ouvrir_vue(&nom, reseaux).map_err(|e| ZoneError::new(numero, e))?);
+                    continue;
+                }
+                let argument = jetons.next().map(|jeton| jeton.texte);
+                match directive.as_str() {
+                    "$ORIGIN" => {
+                        let nom = argument.ok_or_else(|| ZoneError::new(numero, "$ORIGIN sans nom"))?;
+                        origine = Some(nom_absolu(&nom, origine.as_deref()).map_err(|e| ZoneError::new(numero, e))?);
+                    }
+                    "$TTL" => {
+                        let valeur = argument.ok_or_else(|| ZoneError::new(numero, "$TTL sans valeur"))?;
+                        ttl_defaut = Some(parse_duree(&valeur).map_err(|e| ZoneError::new(numero, e))?);
+                    }
+                    "$ENDVIEW" => {
+                        if argument.is_some() {
+                            return Err(ZoneError::new(numero, "arguments en trop après la directive"));
+                        }
+                        vue = None;
+                    }
+                    directive => {
+                        return Err(ZoneError::new(numero, format!("directive non supportée: {}", directive)));
+                    }
+                }
+                if jetons.next().is_some() {
+                    return Err(ZoneError::new(numero, "arguments en trop après la directive"));
+                }
+                continue;
+            }
+
+            // Propriétaire : hérité de la ligne précédente si la ligne commence par un blanc
+            let proprietaire = if ligne.indentee {
+                dernier_proprietaire
+                    .clone()
+                    .ok_or_else(|| ZoneError::new(numero, "aucun nom de propriétaire précédent"))?
+            } else {
+                let jeton = jetons.next().unwrap();
+                nom_absolu(&jeton.texte, origine.as_deref()).map_err(|e| ZoneError::new(numero, e))?
+            };
+            dernier_proprietaire = Some(proprietaire.clone());
+
+            // TTL et classe optionnels, dans un ordre quelconque
+            let mut ttl = None;
+            let mut type_texte = None;
+            for jeton in jetons.by_ref() {
+                if jeton.texte.eq_ignore_ascii_case("IN") {
+                    continue;
+                }
+                if ttl.is_none() && jeton.texte.starts_with(|c: char| c.is_ascii_digit()) {
+                    ttl = Some(parse_duree(&jeton.texte).map_err(|e| ZoneError::new(numero, e))?);
+                    continue;
+                }
+                type_texte = Some(jeton.texte);
+                break;
+            }
+
+            let type_texte = type_texte.ok_or_else(|| ZoneError::new(numero, "type d'enregistrement manquant"))?;
+            let rtype = type_from_str(&type_texte)
+                .ok_or_else(|| ZoneError::new(numero, format!("type ou classe inconnu: {}", type_texte)))?;
+
+            let arguments: Vec<Jeton> = jetons.collect();
+            let rdata = parse_rdata(rtype, &arguments, origine.as_deref()).map_err(|e| ZoneError::new(numero, e))?;
+
+            let ttl = ttl.or(ttl_defaut).unwrap_or(TTL_PAR_DEFAUT);
+            let mut record = DnsAnswer::new(proprietaire, ttl, rdata);
+            record.rtype = rtype;
+            match vue {
+                Some(index) => table.views[index].records.insert(record),
+                None => table.insert(record),
+            }
+        }
+
+        Ok(table)
+    }
+}
+
+impl fmt::Display for RecordTable {
+    /// Écrit la table au format fichier maître, un enregistrement par ligne, suivie des vues
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for record in self.records() {
+            writeln!(f, "{}", record)?;
+        }
+        for vue in &self.views {
+            write!(f, "$VIEW {}", vue.name)?;
+            for reseau in &vue.networks {
+                write!(f, " {}", reseau)?;
+            }
+            writeln!(f)?;
+            write!(f, "{}", vue.records)?;
+            writeln!(f, "$ENDVIEW")?;
+        }
+        Ok(())
+    }
+}
+
+/// Mot d'une ligne de zone, en retenant s'il était entre guillemets
+#[derive(Debug, Clone)]
+struct Jeton {
+    texte: String,
+    guillemets: bool,
+}
+
+/// Ligne logique d'un fichier de zone (les parenthèses peuvent couvrir plusieurs lignes physiques)
+struct LigneLogique {
+    numero: usize,
+    indentee: bool,
+    jetons: Vec<Jeton>,
+}
+
+/// Découpe le texte en lignes logiques en retirant les commentaires
+fn lignes_logiques(texte: &str) -> Result<Vec<LigneLogique>, ZoneError> {
+    let mut lignes = Vec::new();
+    let mut courante: Option<LigneLogique> = None;
+    let mut profondeur = 0usize;
+
+    for (index, ligne) in texte.lines().enumerate() {
+        let numero = index + 1;
+        let logique = courante.get_or_insert_with(|| LigneLogique {
+            numero,
+            indentee: ligne.starts_with([' ', '\t']),
+            jetons: Vec::new(),
+        });
+
+        let mut caracteres = ligne.chars().peekable();
+        while let Some(c) = caracteres.next() {
+            match c {
+                ';' => break,
+                '(' => profondeur += 1,
+                ')' => {
+                    profondeur = profondeur
+                        .checked_sub(1)
+                        .ok_or_else(|| ZoneError::new(numero, "parenthèse fermante sans ouvrante"))?;
+                }
+                '"' => {
+                    let mut texte = String::new();
+                    let mut ferme = false;
+                    while let Some(c) = caracteres.next() {
+                        match c {
+                            // L'échappement est conservé : il est interprété avec les données
+                            '\\' => {
+                                texte.push(c);
+                                texte.extend(caracteres.next());
+                            }
+                            '"' => {
+                                ferme = true;
+                                break;
+                            }
+                            _ => texte.push(c),
+                        }
+                    }
+                    if !ferme {
+                        return Err(ZoneError::new(numero, "guillemet non fermé"));
+                    }
+                    logique.jetons.push(Jeton { texte, guillemets: true });
+                }
+                c if c.is_whitespace() => {}
+                _ => {
+                    let mut texte = String::from(c);
+                    while let Some(&suivant) = caracteres.peek() {
+                        if suivant.is_whitespace() || matches!(suivant, ';' | '(' | ')' | '"') {
+                            break;
+                        }
+                        texte.push(suivant);
+                        caracteres.next();
+                    }
+                    logique.jetons.push(Jeton { texte, guillemets: false });
+                }
+            }
+        }
+
+        if profondeur == 0 {
+            lignes.extend(courante.take());
+        }
+    }
+
+    if let Some(ligne) = courante {
+        if profondeur > 0 {
+            return Err(ZoneError::new(ligne.numero, "parenthèse ouvrante non fermée"));
+        }
+    }
+    Ok(lignes)
+}
+
+/// Octets d'une chaîne de caractères du fichier de zone : `\X` donne le caractère X,
+/// `\DDD` l'octet de valeur décimale DDD (RFC 1035 §5.1)
+fn chaine_caracteres(texte: &str) -> Result<Vec<u8>, String> {
+    let mut octets = Vec::with_capacity(texte.len());
+    let mut reste = texte.as_bytes();
+    while let Some((&octet, suite)) = reste.split_first() {
+        reste = suite;
+        if octet != b'\\' {
+            octets.push(octet);
+            continue;
+        }
+        match reste {
+            [a, b, c, suite @ ..] if [a, b, c].iter().all(|chiffre| chiffre.is_ascii_digit()) => {
+                let valeur = std::str::from_utf8(&reste[..3]).unwrap().parse::<u16>().unwrap();
+                octets.push(u8::try_from(valeur).map_err(|_| format!("échappement \\{} invalide", valeur))?);
+                reste = suite;
+            }
+            [suivant, suite @ ..] => {
+                octets.push(*suivant);
+                reste = suite;
+            }
+            [] => return Err(format!("échappement incomplet: {}", texte)),
+        }
+    }
+    Ok(octets)
+}
+
+/// Clé d'indexation d'un nom : insensible à la casse (RFC 4343) et sans point final
+pub(crate) fn cle(nom: &str) -> String {
+    nom.trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// Convertit un nom du fichier de zone en nom absolu validé (sans point final)
+fn nom_absolu(nom: &str, origine: Option<&str>) -> Result<String, String> {
+    let absolu = if nom == "@" {
+        origine.map(|o| o.to_string()).ok_or_else(|| "'@' utilisé sans $ORIGIN".to_string())?
+    } else if nom == "." {
+        String::new()
+    } else if let Some(absolu) = nom.strip_suffix('.') {
+        absolu.to_string()
+    } else {
+        match origine {
+            Some("") => nom.to_string(),
+            Some(origine) => format!("{}.{}", nom, origine),
+            None => return Err(format!("nom relatif '{}' sans $ORIGIN", nom)),
+        }
+    };
+    DnsQuestion::validate_domain_name(&absolu).map_err(|e| e.to_string())
+}
+
+/// Parse une durée en secondes, avec unités optionnelles (ex : 3600, 1h, 1h30m, 2d)
+fn parse_duree(texte: &str) -> Result<u32, String> {
+    if let Ok(secondes) = texte.parse::<u32>() {
+        return Ok(secondes);
+    }
+
+    let erreur = || format!("durée invalide: {}", texte);
+    let mut total: u32 = 0;
+    let mut nombre = String::new();
+    for c in texte.chars() {
+        if c.is_ascii_digit() {
+            nombre.push(c);
+            continue;
+        }
+        let multiplicateur = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            'w' => 604800,
+            _ => return Err(erreur()),
+        };
+        let valeur: u32 = nombre.parse().map_err(|_| erreur())?;
+        total = valeur
+            .checked_mul(multiplicateur)
+            .and_then(|v| total.checked_add(v))
+            .ok_or_else(erreur)?;
+        nombre.clear();
+    }
+    if !nombre.is_empty() {
+        return Err(erreur());
+    }
+    Ok(total)
+}
+
+/// Parse les données d'un enregistrement selon son type
+fn parse_rdata(rtype: u16, arguments: &[Jeton], origine: Option<&str>) -> Result<RData, String> {
+    let textes: Vec<&str> = arguments.iter().map(|jeton| jeton.texte.as_str()).collect();
+
+    // Format générique de la RFC 3597 : \# longueur hexadécimal
+    if textes.first() == Some(&"\\#") {
+        let longueur: usize = textes
+            .get(1)
+            .and_then(|l| l.parse().ok())
+            .ok_or("longueur invalide dans le format générique")?;
+        let hex: String = textes[2..].concat();
+        if !hex.is_ascii() {
+            return Err("données hexadécimales invalides".to_string());
+        }
+        if hex.len() != longueur * 2 {
+            return Err("données hexadécimales de longueur incorrecte".to_string());
+        }
+        let octets = (0..longueur)
+            .map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| "données hexadécimales invalides".to_string())?;
+        return Ok(RData::Unknown(octets));
+    }
+
+    let attendre = |nombre: usize| {
+        if textes.len() == nombre {
+            Ok(())
+        } else {
+            Err(format!("{} argument(s) attendu(s), {} trouvé(s)", nombre, textes.len()))
+        }
+    };
+    let nom = |texte: &str| nom_absolu(texte, origine);
+    let entier = |texte: &str| texte.parse::<u16>().map_err(|_| format!("entier invalide: {}", texte));
+
+    match rtype {
+        DNS_TYPE_A => {
+            attendre(1)?;
+            Ok(RData::A(textes[0].parse().map_err(|_| format!("adresse IPv4 invalide: {}", textes[0]))?))
+        }
+        DNS_TYPE_AAAA => {
+            attendre(1)?;
+            Ok(RData::Aaaa(textes[0].parse().map_err(|_| format!("adresse IPv6 invalide: {}", textes[0]))?))
+        }
+        DNS_TYPE_CNAME => {
+            attendre(1)?;
+            Ok(RData::Cname(nom(textes[0])?))
+        }
+        DNS_TYPE_NS => {
+            attendre(1)?;
+            Ok(RData::Ns(nom(textes[0])?))
+        }
+        DNS_TYPE_PTR => {
+            attendre(1)?;
+            Ok(RData::Ptr(nom(textes[0])?))
+        }
+        DNS_TYPE_MX => {
+            attendre(2)?;
+            Ok(RData::Mx { preference: entier(textes[0])?, exchange: nom(textes[1])? })
+        }
+        DNS_TYPE_TXT => {
+            if textes.is_empty() {
+                return Err("au moins une chaîne TXT est attendue".to_string());
+            }
+            Ok(RData::Txt(textes.iter().map(|texte| chaine_caracteres(texte)).collect::<Result<_, _>>()?))
+        }
+        DNS_TYPE_SOA => {
+            attendre(7)?;
+            let mut valeurs = [0u32; 5];
+            for (valeur, texte) in valeurs.iter_mut().zip(&textes[2..]) {
+                *valeur = parse_duree(texte)?;
+            }
+            Ok(RData::Soa {
+                mname: nom(textes[0])?,
+                rname: nom(textes[1])?,
+                serial: valeurs[0],
+                refresh: valeurs[1],
+                retry: valeurs[2],
+                expire: valeurs[3],
+                minimum: valeurs[4],
+            })
+        }
+        DNS_TYPE_SRV => {
+            attendre(4)?;
+            Ok(RData::Srv {
+                priority: entier(textes[0])?,
+                weight: entier(textes[1])?,
+                port: entier(textes[2])?,
+                target: nom(textes[3])?,
+            })
+        }
+        _ => Err("seul le format générique \\# est accepté pour ce type".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ZONE: &str = r#"
+$ORIGIN exemple.com.
+$TTL 1h
+@   IN SOA ns1 admin (
+        2024010101 ; serial
+        3600 600 1w 300 )
+    IN NS  ns1
+ns1 60 IN A 192.168.1.1
+www    A   192.168.1.101 ; commentaire
+       AAAA 2001:db8::101
+txt    TXT "bonjour \"monde\"" "; pas un commentaire"
+ext.autre.org. IN CNAME www
+"#;
+
+    #[test]
+    fn test_parse_zone_complete() {
+        let table = RecordTable::parse(ZONE, None).unwrap();
+        assert_eq!(table.len(), 7);
+
+        let soa = &table.get("exemple.com").unwrap()[0];
+        assert_eq!(soa.ttl, 3600);
+        assert_eq!(soa.rdata, RData::Soa {
+            mname: "ns1.exemple.com".to_string(),
+            rname: "admin.exemple.com".to_string(),
+            serial: 2024010101,
+            refresh: 3600,
+            retry: 600,
+            expire: 604800,
+            minimum: 300,
+        });
+
+        assert_eq!(table.get("ns1.exemple.com").unwrap()[0].ttl, 60);
+        let www = table.get("www.exemple.com").unwrap();
+        assert_eq!(www.len(), 2);
+        assert_eq!(www[1].rdata, RData::Aaaa("2001:db8::101".parse().unwrap()));
+        assert_eq!(
+            table.get("txt.exemple.com").unwrap()[0].rdata,
+            RData::Txt(vec![b"bonjour \"monde\"".to_vec(), b"; pas un commentaire".to_vec()])
+        );
+        assert_eq!(table.get("ext.autre.org").unwrap()[0].rdata, RData::Cname("www.exemple.com".to_string()));
+
+        // Échappements \DDD : l'affichage d'un TXT se relit à l'identique
+        let txt = RData::Txt(vec![vec![0xff, 0xfe, b'"', b'\\', b';'], Vec::new()]);
+        let relue = RecordTable::parse(&format!("a.com. TXT {}\n", txt), None).unwrap();
+        assert_eq!(relue.get("a.com").unwrap()[0].rdata, txt);
+        assert!(RecordTable::parse("a.com. TXT \"\\256\"\n", None).is_err());
+    }
+
+    #[test]
+    fn test_erreurs_avec_numero_de_ligne() {
+        let erreur = RecordTable::parse("$ORIGIN exemple.com.\nwww A 300.1.1.1\n", None).unwrap_err();
+        assert_eq!(erreur.line, 2);
+
+        let erreur = RecordTable::parse("www A 1.2.3.4\n", None).unwrap_err();
+        assert_eq!(erreur.line, 1);
+        assert!(erreur.message.contains("$ORIGIN"));
+
+        let erreur = RecordTable::parse("a.com. SOA ns admin ( 1 2 3\n4 5\n", None).unwrap_err();
+        assert_eq!(erreur.line, 1);
+        assert_eq!(erreur.to_string(), "ligne 1: parenthèse ouvrante non fermée");
+
+        let erreur = RecordTable::parse("$ORIGIN exemple.com.\nwww A 1.2.3.4\nmauvais!nom A 1.2.3.4\n", None).unwrap_err();
+        assert_eq!(erreur.line, 3);
+        assert!(erreur.message.contains("caractère non autorisé"));
+
+        // Format générique avec un caractère non ASCII : erreur et non panique
+        let erreur = RecordTable::parse("x.com. 60 TYPE99 \\# 2 aé1\n", None).unwrap_err();
+        assert_eq!(erreur.to_string(), "ligne 1: données hexadécimales invalides");
+    }
+
+    #[test]
+    fn test_jokers_rfc4592() {
+        let zone = "$ORIGIN exemple.com.\n*.exemple.com. TXT \"joker\"\nwww A 1.1.1.1\n_sip._udp SRV 0 0 5060 www\n";
+        let table = RecordTable::parse(zone, None).unwrap();
+
+        // Nom absent : synthétisé depuis le joker, au nom demandé
+        let synthetise = table.lookup("Inconnu.exemple.com").unwrap();
+        assert_eq!(synthetise[0].name, "Inconnu.exemple.com");
+        assert_eq!(synthetise[0].rdata, RData::Txt(vec![b"joker".to_vec()]));
+        assert!(table.lookup("a.b.exemple.com").is_some());
+
+        // Le joker ne s'applique ni sous un nom existant, ni à un nœud vide
+        assert!(table.lookup("a.www.exemple.com").is_none());
+        assert_eq!(table.lookup("_udp.exemple.com"), Some(Vec::new()));
+        assert_eq!(table.resolve("www.exemple.com", DNS_TYPE_TXT).status, LookupStatus::NoData);
+
+        // Le nœud vide disparaît avec le dernier nom qu'il couvre : le joker s'y applique alors
+        let mut table = table;
+        let srv = table.get("_sip._udp.exemple.com").unwrap()[0].clone();
+        assert!(table.remove(&srv));
+        assert_eq!(table.lookup("_udp.exemple.com").unwrap()[0].rtype, DNS_TYPE_TXT);
+        assert!(table.lookup("www.exemple.com").is_some_and(|records| records.len() == 1));
+    }
+
+    #[test]
+    fn test_chaine_cname() {
+        let zone = "$ORIGIN exemple.com.\na CNAME b\nb CNAME c\nc A 1.2.3.4\nx CNAME absent\nboucle CNAME boucle\n";
+        let table = RecordTable::parse(zone, None).unwrap();
+
+        let resolution = table.resolve("A.exemple.com", DNS_TYPE_A);
+        assert_eq!(resolution.status, LookupStatus::Found);
+        let noms: Vec<&str> = resolution.answers.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(noms, ["A.exemple.com", "b.exemple.com", "c.exemple.com"]);
+        assert_eq!(resolution.final_name, "c.exemple.com");
+
+        // Le type CNAME demandé explicitement arrête la chaîne
+        assert_eq!(table.resolve("a.exemple.com", DNS_TYPE_CNAME).answers.len(), 1);
+
+        let resolution = table.resolve("x.exemple.com", DNS_TYPE_A);
+        assert_eq!((resolution.status, resolution.answers.len()), (LookupStatus::NxDomain, 1));
+        assert_eq!(table.resolve("boucle.exemple.com", DNS_TYPE_A).status, LookupStatus::CnameLoop);
+    }
+
+    #[test]
+    fn test_zones_soa_ns() {
+        let table = RecordTable::parse(ZONE, None).unwrap();
+        assert_eq!(table.find_zone("WWW.exemple.com").unwrap().name, "exemple.com");
+        assert!(table.find_zone("exemple.org").is_none());
+        assert!(table.validate().is_ok());
+
+        // Un sommet de zone doit porter au moins un NS
+        let sans_ns = RecordTable::parse("a.com. SOA ns.a.com. admin.a.com. 1 2 3 4 5\n", None).unwrap();
+        assert!(sans_ns.validate().unwrap_err().message.contains("aucun NS"));
+    }
+
+    #[test]
+    fn test_synthese_ptr() {
+        let zone = "$ORIGIN exemple.com.\nwww A 10.0.0.1\n    AAAA fd00::1\nweb A 10.0.0.1\n*.dev A 10.0.0.9\n\
+                    2.0.0.10.in-addr.arpa. PTR manuel.exemple.com.\nautre A 10.0.0.2\n";
+        let mut table = RecordTable::parse(zone, None).unwrap();
+
+        // 10.0.0.1 (deux noms) et fd00::1 ; 10.0.0.2 a déjà un PTR, le joker est ignoré
+        assert_eq!(table.synthesize_ptr(), 3);
+        assert_eq!(table.get("1.0.0.10.in-addr.arpa").unwrap().len(), 2);
+        assert_eq!(table.get("2.0.0.10.in-addr.arpa").unwrap().len(), 1);
+        assert!(table.get("9.0.0.10.in-addr.arpa").is_none());
+        let inverse_v6 = reverse_name("fd00::1".parse().unwrap());
+        assert_eq!(table.get(&inverse_v6).unwrap()[0].rdata, RData::Ptr("www.exemple.com".to_string()));
+
+        // Autorité sur le seul nom inverse, ou sur la zone inverse déclarée si elle existe
+        let zone = "a.com. SOA ns.a.com. admin.a.com. 1 2 3 4 5\na.com. NS ns.a.com.\nns.a.com. A 8.8.8.8\n\
+                    1.10.in-addr.arpa. SOA ns.a.com. admin.a.com. 1 2 3 4 5\n1.10.in-addr.arpa. NS ns.a.com.\n\
+                    hote.a.com. A 10.1.0.5\n";
+        let mut table = RecordTable::parse(zone, None).unwrap();
+        assert_eq!(table.synthesize_ptr(), 2);
+        assert_eq!(table.find_zone("8.8.8.8.in-addr.arpa").unwrap().name, "8.8.8.8.in-addr.arpa");
+        assert!(table.find_zone("4.4.8.8.in-addr.arpa").is_none());
+        assert_eq!(table.find_zone("5.0.1.10.in-addr.arpa").unwrap().name, "1.10.in-addr.arpa");
+        assert!(table.get("5.0.1.10.in-addr.arpa").unwrap().iter().all(|r| r.rtype == DNS_TYPE_PTR));
+    }
+
+    #[test]
+    fn test_recherche_insensible_a_la_casse() {
+        let table = RecordTable::parse("Www.Exemple.COM. A 1.2.3.4\ncafé.fr. A 5.6.7.8\n", None).unwrap();
+        assert_eq!(table.get("www.exemple.com").unwrap()[0].name, "Www.Exemple.COM");
+        assert!(table.get("WWW.EXEMPLE.COM.").is_some());
+        assert!(table.get("xn--caf-dma.fr").is_some());
+    }
+
+    #[test]
+    fn test_validation_cname() {
+        let table = RecordTable::parse("a.com. CNAME b.com.\na.com. A 1.2.3.4\n", None).unwrap();
+        assert!(table.validate().is_err());
+        assert!(RecordTable::parse(ZONE, None).unwrap().validate().is_ok());
+    }
+
+    #[test]
+    fn test_diff_entre_versions() {
+        let ancienne = RecordTable::parse("a.com. A 1.1.1.1\nb.com. A 2.2.2.2\n", None).unwrap();
+        let nouvelle = RecordTable::parse("a.com. A 1.1.1.1\nc.com. A 3.3.3.3\n", None).unwrap();
+
+        let diff = ancienne.diff(&nouvelle);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].name, "c.com");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].name, "b.com");
+        assert!(nouvelle.diff(&nouvelle).is_empty());
+    }
+
+    #[test]
+    fn test_vues_par_reseau_client() {
+        let zone = "$ORIGIN exemple.com.\nwww A 203.0.113.10\nmail A 203.0.113.25\nftp CNAME www\n\
+                    $VIEW interne 127.0.0.0/8 10.0.0.0/8\nwww A 10.0.0.10\n     A 10.0.0.11\nftp A 10.0.0.21\n$ENDVIEW\n\
+                    intranet A 203.0.113.99\n";
+        let table = RecordTable::parse(zone, None).unwrap();
+        table.validate().unwrap();
+        assert_eq!(table.len(), 4);
+
+        assert!(table.view_for("192.0.2.1".parse().unwrap()).is_none());
+        let vue = table.view_for("10.1.2.3".parse().unwrap()).unwrap();
+        assert_eq!(vue.name, "interne");
+        let interne = table.with_view(vue);
+        assert_eq!(interne.resolve("www.exemple.com", DNS_TYPE_A).answers.len(), 2);
+        assert_eq!(interne.resolve("mail.exemple.com", DNS_TYPE_A).answers[0].rdata, RData::A("203.0.113.25".parse().unwrap()));
+        // Le A de la vue remplace le CNAME commun
+        assert_eq!(interne.get("ftp.exemple.com").unwrap().len(), 1);
+        assert_eq!(interne.get("ftp.exemple.com").unwrap()[0].rtype, DNS_TYPE_A);
+
+        // Les vues survivent à l'écriture de la table
+        let relue = RecordTable::parse(&table.to_string(), None).unwrap();
+        assert!(table.diff(&relue).is_empty());
+        assert_eq!(relue.views()[0].networks, vue.networks);
+
+        let erreur = RecordTable::parse("$VIEW vide\n", None).unwrap_err();
+        assert_eq!(erreur.line, 1);
+        assert!(RecordTable::parse("$VIEW v 10.0.0.0/33\n", None).is_err());
+    }
+
+    #[test]
+    fn test_affichage_relu() {
+        let table = RecordTable::parse(ZONE, None).unwrap();
+        let relue = RecordTable::parse(&table.to_string(), None).unwrap();
+        let originaux: Vec<String> = table.records().map(|r| r.to_string()).collect();
+        let relus: Vec<String> = relue.records().map(|r| r.to_string()).collect();
+        assert_eq!(originaux, relus);
+    }
+}